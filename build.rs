@@ -0,0 +1,35 @@
+// Generates the table of embedded resources used by src/resources.rs.
+//
+// With the `embed-resources` feature, every file in resources/ is
+// compiled into the binaries with include_bytes!.  Without it the
+// table is empty and everything is loaded from disk.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let mut bundle = fs::File::create(Path::new(&out_dir).join("bundle.rs")).unwrap();
+
+    writeln!(bundle, "static BUNDLE: &[(&str, &[u8])] = &[").unwrap();
+    if env::var_os("CARGO_FEATURE_EMBED_RESOURCES").is_some() {
+        let root = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let mut names: Vec<String> = fs::read_dir(Path::new(&root).join("resources"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        for name in names {
+            writeln!(bundle,
+                     "    (\"resources/{0}\", include_bytes!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/resources/{0}\"))),",
+                     name).unwrap();
+        }
+    }
+    writeln!(bundle, "];").unwrap();
+
+    println!("cargo:rerun-if-changed=resources");
+}
@@ -0,0 +1,312 @@
+//! A menu of every lesson, so you don't have to remember which
+//! `cargo run --bin lessonNN` is which.
+//!
+//! Each lesson's source file is compiled into this binary as a module,
+//! and picking one calls its `run` function.  SDL can only be
+//! initialized once at a time, so the menu shuts SDL down completely
+//! before the lesson starts, and starts it again when the lesson quits.
+
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use sdl2::ttf::Sdl2TtfContext;
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+use lazy_foo::texture::LTexture;
+
+// The lessons' `main` functions are never called from here
+#[allow(dead_code)] #[path = "../lesson01/main.rs"] mod lesson01;
+#[allow(dead_code)] #[path = "../lesson02/main.rs"] mod lesson02;
+#[allow(dead_code)] #[path = "../lesson03/main.rs"] mod lesson03;
+#[allow(dead_code)] #[path = "../lesson04/main.rs"] mod lesson04;
+#[allow(dead_code)] #[path = "../lesson05/main.rs"] mod lesson05;
+#[allow(dead_code)] #[path = "../lesson06/main.rs"] mod lesson06;
+#[allow(dead_code)] #[path = "../lesson07/main.rs"] mod lesson07;
+#[allow(dead_code)] #[path = "../lesson08/main.rs"] mod lesson08;
+#[allow(dead_code)] #[path = "../lesson09/main.rs"] mod lesson09;
+#[allow(dead_code)] #[path = "../lesson10/main.rs"] mod lesson10;
+#[allow(dead_code)] #[path = "../lesson11/main.rs"] mod lesson11;
+#[allow(dead_code)] #[path = "../lesson12/main.rs"] mod lesson12;
+#[allow(dead_code)] #[path = "../lesson13/main.rs"] mod lesson13;
+#[allow(dead_code)] #[path = "../lesson14/main.rs"] mod lesson14;
+#[allow(dead_code)] #[path = "../lesson15/main.rs"] mod lesson15;
+#[allow(dead_code)] #[path = "../lesson16/main.rs"] mod lesson16;
+#[allow(dead_code)] #[path = "../lesson17/main.rs"] mod lesson17;
+#[allow(dead_code)] #[path = "../lesson18/main.rs"] mod lesson18;
+#[allow(dead_code)] #[path = "../lesson19/main.rs"] mod lesson19;
+#[allow(dead_code)] #[path = "../lesson32/main.rs"] mod lesson32;
+#[allow(dead_code)] #[path = "../lesson35/main.rs"] mod lesson35;
+#[allow(dead_code)] #[path = "../lesson36/main.rs"] mod lesson36;
+#[allow(dead_code)] #[path = "../lesson37/main.rs"] mod lesson37;
+#[allow(dead_code)] #[path = "../lesson39/main.rs"] mod lesson39;
+#[allow(dead_code)] #[path = "../lesson40/main.rs"] mod lesson40;
+#[allow(dead_code)] #[path = "../lesson41/main.rs"] mod lesson41;
+#[allow(dead_code)] #[path = "../lesson42/main.rs"] mod lesson42;
+#[allow(dead_code)] #[path = "../lesson43/main.rs"] mod lesson43;
+#[allow(dead_code)] #[path = "../lesson54/main.rs"] mod lesson54;
+#[allow(dead_code)] #[path = "../lesson55/main.rs"] mod lesson55;
+
+/// A lesson's `run` function
+type Entry = fn(&Options) -> Result<()>;
+
+/// Every lesson's menu entry and entry point, in order.
+const LESSONS: &[(&str, Entry)] = &[
+    ("01 - Hello SDL", lesson01::run),
+    ("02 - Getting an Image on the Screen", lesson02::run),
+    ("03 - Event Driven Programming", lesson03::run),
+    ("04 - Key Presses", lesson04::run),
+    ("05 - Optimized Surface Loading", lesson05::run),
+    ("06 - Extension Libraries", lesson06::run),
+    ("07 - Texture Loading and Rendering", lesson07::run),
+    ("08 - Geometry Rendering", lesson08::run),
+    ("09 - The Viewport", lesson09::run),
+    ("10 - Color Keying", lesson10::run),
+    ("11 - Clip Rendering and Sprite Sheets", lesson11::run),
+    ("12 - Color Modulation", lesson12::run),
+    ("13 - Alpha Blending", lesson13::run),
+    ("14 - Animated Sprites and Vsync", lesson14::run),
+    ("15 - Rotation and Flipping", lesson15::run),
+    ("16 - True Type Fonts", lesson16::run),
+    ("17 - Mouse Events", lesson17::run),
+    ("18 - Key States", lesson18::run),
+    ("19 - Gamepads and Joysticks", lesson19::run),
+    ("32 - Text Input and Clipboard Handling", lesson32::run),
+    ("35 - Window Events", lesson35::run),
+    ("36 - Multiple Windows", lesson36::run),
+    ("37 - Multiple Displays", lesson37::run),
+    ("39 - Tiling", lesson39::run),
+    ("40 - Texture Manipulation", lesson40::run),
+    ("41 - Bitmap Fonts", lesson41::run),
+    ("42 - Texture Streaming", lesson42::run),
+    ("43 - Render to Texture", lesson43::run),
+    ("54 - Touches", lesson54::run),
+    ("55 - Multitouch", lesson55::run),
+];
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const FONT_FILE: &str = "resources/lazy.ttf";
+const FONT_SIZE: u16 = 22;
+
+// Layout of the menu, in pixels
+const MARGIN: i32 = 16;
+const LIST_TOP: i32 = 64;
+const ROW_HEIGHT: i32 = 32;
+const VISIBLE_ROWS: usize = ((HEIGHT as i32 - LIST_TOP - MARGIN) / ROW_HEIGHT) as usize;
+
+/// Which lesson is highlighted, and how far the list has scrolled.
+struct MenuState {
+    selected: usize,
+    scroll: usize,
+}
+
+impl MenuState {
+
+    fn new(selected: usize) -> MenuState {
+        let mut state = MenuState { selected: 0, scroll: 0 };
+        state.select(selected);
+        state
+    }
+
+    /// Highlight `index` (clamped to the list), scrolling it into view.
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(LESSONS.len() - 1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + VISIBLE_ROWS {
+            self.scroll = self.selected + 1 - VISIBLE_ROWS;
+        }
+    }
+
+    /// Move the highlight up (negative) or down (positive) by `rows`.
+    fn step(&mut self, rows: i32) {
+        let index = self.selected as i32 + rows;
+        self.select(index.max(0) as usize);
+    }
+
+    /// Scroll the list without moving the highlight.
+    fn scroll_by(&mut self, rows: i32) {
+        let last = LESSONS.len().saturating_sub(VISIBLE_ROWS) as i32;
+        self.scroll = (self.scroll as i32 + rows).max(0).min(last) as usize;
+    }
+
+    /// The lesson drawn at window coordinate `y`, if any.
+    fn lesson_at(&self, y: i32) -> Option<usize> {
+        if y < LIST_TOP {
+            return None;
+        }
+        let row = ((y - LIST_TOP) / ROW_HEIGHT) as usize;
+        let index = self.scroll + row;
+        if row < VISIBLE_ROWS && index < LESSONS.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2TtfContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "Lazy Foo's SDL2 Tutorials", WIDTH, HEIGHT)?;
+    let ttf = sdl2::ttf::init()
+        .map_err(|err| Error::init("sdl2_ttf", err))?;
+
+    Ok((sdl, win, ttf))
+}
+
+/// Render the heading and one texture per lesson title.
+fn load_media(renderer: &Renderer, ttf: &Sdl2TtfContext) -> Result<(LTexture, Vec<LTexture>)> {
+    let font_path = Path::new(FONT_FILE);
+    let font = resources::load_font(ttf, font_path, FONT_SIZE)?;
+
+    let black = Color::RGB(0, 0, 0);
+    let heading = LTexture::load_from_rendered_text(
+        renderer, &font, "Pick a lesson (Enter or click to run, Esc to quit)", black)?;
+    let mut entries = Vec::with_capacity(LESSONS.len());
+    for &(title, _) in LESSONS {
+        entries.push(LTexture::load_from_rendered_text(renderer, &font, title, black)?);
+    }
+    Ok((heading, entries))
+}
+
+/// Show the menu with lesson `selected` highlighted.  Returns the
+/// lesson that was picked, or None if the menu was closed.
+///
+/// SDL is shut down again by the time this returns, so the caller is
+/// free to run the lesson.
+fn menu(options: &Options, selected: usize) -> Result<Option<usize>> {
+
+    let (sdl_context, window, ttf_context) = init(options)?;
+
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let (heading, entries) = load_media(&renderer, &ttf_context)?;
+
+    let mut state = MenuState::new(selected);
+    let mut chosen = None;
+    let mut running: bool = true;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    while running {
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit {..} => {
+                    running = false
+                },
+                Event::KeyDown { keycode: Some(k), .. } => match k {
+                    Keycode::Escape | Keycode::Q => running = false,
+                    Keycode::Up => state.step(-1),
+                    Keycode::Down => state.step(1),
+                    Keycode::PageUp => state.step(-(VISIBLE_ROWS as i32)),
+                    Keycode::PageDown => state.step(VISIBLE_ROWS as i32),
+                    Keycode::Home => state.select(0),
+                    Keycode::End => state.select(LESSONS.len() - 1),
+                    Keycode::Return | Keycode::KpEnter | Keycode::Space => {
+                        chosen = Some(state.selected);
+                        running = false;
+                    },
+                    _ => {}
+                },
+                // Hovering highlights a lesson, clicking runs it
+                Event::MouseMotion { y, .. } => {
+                    if let Some(index) = state.lesson_at(y) {
+                        state.select(index);
+                    }
+                },
+                Event::MouseButtonDown { mouse_btn: MouseButton::Left, y, .. } => {
+                    if let Some(index) = state.lesson_at(y) {
+                        chosen = Some(index);
+                        running = false;
+                    }
+                },
+                // The wheel reports positive y when scrolled away from the user
+                Event::MouseWheel { y, .. } => state.scroll_by(-y),
+                _ => {}
+            }
+        }
+
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        heading.render(&mut renderer, MARGIN, (LIST_TOP - heading.height as i32) / 2, None)?;
+
+        let visible = entries.iter().enumerate().skip(state.scroll).take(VISIBLE_ROWS);
+        for (row, (index, entry)) in visible.enumerate() {
+            let y = LIST_TOP + row as i32 * ROW_HEIGHT;
+            if index == state.selected {
+                renderer.set_draw_color(Color::RGB(0xcc, 0xe5, 0xff));
+                renderer.fill_rect(Rect::new(MARGIN / 2, y, WIDTH - MARGIN as u32, ROW_HEIGHT as u32))
+                    .expect("Could not draw the highlight!");
+            }
+            entry.render(&mut renderer, MARGIN, y + (ROW_HEIGHT - entry.height as i32) / 2, None)?;
+        }
+
+        // Draw a scroll bar when not every lesson fits
+        if LESSONS.len() > VISIBLE_ROWS {
+            let track = VISIBLE_ROWS as i32 * ROW_HEIGHT;
+            let top = LIST_TOP + track * state.scroll as i32 / LESSONS.len() as i32;
+            let length = track * VISIBLE_ROWS as i32 / LESSONS.len() as i32;
+            renderer.set_draw_color(Color::RGB(0x80, 0x80, 0x80));
+            renderer.fill_rect(Rect::new(WIDTH as i32 - MARGIN / 2, top, 4, length as u32))
+                .expect("Could not draw the scroll bar!");
+        }
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    Ok(chosen)
+}
+
+/// Show the menu, and run lessons from it until it is closed.  The
+/// launcher's command line options apply to the menu; the lessons it
+/// runs only get the ones about the window, so one lesson's capture or
+/// recording isn't overwritten by the next.
+fn run(options: &Options) -> Result<()> {
+    let lesson_options = options.display_options();
+    let mut selected = 0;
+    while let Some(lesson) = menu(options, selected)? {
+        // A lesson that fails shouldn't take the menu down with it
+        let (title, run_lesson) = LESSONS[lesson];
+        if let Err(err) = run_lesson(&lesson_options) {
+            eprintln!("launcher: lesson {}: {}", title, err);
+        }
+        selected = lesson;
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("launcher: {}", err);
+        process::exit(1);
+    }
+}
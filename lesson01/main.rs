@@ -1,43 +1,54 @@
 extern crate sdl2;
+extern crate lazy_foo;
 
 use sdl2::pixels::Color;
+use std::process;
 use std::time::Duration;
 use std::thread::sleep;
 
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+
 // Set Screen dimensions
 const WIDTH:  u32 = 640;
 const HEIGHT: u32 = 480;
 
-fn main() {
+// Each lesson does its work in a public `run` function rather than in
+// `main`, so that the launcher can pull the lesson in as a module and
+// run it without starting a new process.
+// This lesson spells out the error handling that '?' does for us later.
+#[allow(clippy::question_mark)]
+pub fn run(options: &Options) -> Result<()> {
 
     // Initialize SDL
-    // Note that we can just call:
-    // let sdl_context = sdl2::init(sdl2::INIT_VIDEO).unwrap();
+    // Note that we could just call:
+    // let sdl_context = sdl2::init().unwrap();
     // because 'unwrap' will panic if the result of the init call is not Ok, otherwise
     // it will unwrap Ok and assign the result to 'sdl_context'
-    // We eschew .unwrap() here so we can output the exact SDL error that caused the failure.
+    // We eschew .unwrap() here so we can report the exact SDL error that caused the
+    // failure, and tell the caller which subsystem it came from.  Returning early
+    // with Err(...) like this is exactly what the '?' operator does, which is what
+    // the later lessons use.
     let sdl_context = match sdl2::init() {
         Ok(sdl_context) => sdl_context,
-        Err(err) => panic!("SDL could not initialize!  SDL_Error: {}", err),
+        Err(err) => return Err(Error::init("SDL", err)),
     };
 
     // Get a handle to the underlying video subsystem
     let video = match sdl_context.video() {
         Ok(video) => video,
-        Err(err) => panic!("Could not obtain handle to the video subsystem! SDL_Error: {}", err),
+        Err(err) => return Err(Error::init("video subsystem", err)),
     };
 
     // Create a Window
-    // Window::new and sdl2::init (and other funcs return an SdlResult, which
-    // is just a wrapper around Result<T, string>.  Result can return one
-    // of two values: Ok(T), or Err(string).  Use match to unwrap them.
-    let window = match video.window("SDL Tutorial 1", WIDTH, HEIGHT)
-        .position_centered()
-        .opengl()
-        .build() {
-            Ok(window) => window,
-            Err(err) => panic!("SDL could not create a window! SDL_Error: {}", err),
-        };
+    // Options (see src/options.rs) holds the flags from the command line
+    // - window size, fullscreen and so on - and hands them to SDL's
+    // WindowBuilder.  It returns a Result as well, so a failure is
+    // passed back up with the same match as above.
+    let window = match options.window(&video, "SDL Tutorial 1", WIDTH, HEIGHT) {
+        Ok(window) => window,
+        Err(err) => return Err(err),
+    };
 
     // There's a major deviation from Lazy Foo's Lesson 1 here,
     // because rust-sdl2 (presumably for safety reasons) doesn't let you access
@@ -46,23 +57,34 @@ fn main() {
     // pump so it can verify it's not running.  :-/
     // Instead, we'll obtain a renderer, and use that to update
     // the main window.
-    let mut renderer = match window.renderer().build() {
+    let mut renderer = match options.renderer(window, WIDTH, HEIGHT) {
         Ok(renderer) => renderer,
-        Err(err) => panic!("SDL could not create a renderer! SDL_Error: {}", err),
+        Err(err) => return Err(err),
     };
 
     // Use the renderer it to clear and render the screen
+    let mut capture = options.frame_capture();
     renderer.set_draw_color(Color::RGB(0, 0, 0));
     renderer.clear();
+    capture.frame(&renderer)?;
     renderer.present();
 
-    // Pause for two seconds
-    sleep(Duration::new(2,0));
-    
+    // Pause for two seconds, unless nobody is watching
+    if !capture.is_headless() {
+        sleep(Duration::new(2,0));
+    }
+
     // Quit SDL Subsystems
     // Note that we don't have to explicitly call SDL_Quit, as `sdl_context`
     // calls it as it gets dropped
+    Ok(())
+}
+
+fn main() {
+    // Report anything that went wrong and exit with a failure code,
+    // rather than panicking.
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson01: {}", err);
+        process::exit(1);
+    }
 }
-    
-            
-    
@@ -1,45 +1,43 @@
 extern crate sdl2;
-
-use sdl2::surface::Surface;
+extern crate lazy_foo;
 
 use std::path::Path;
+use std::process;
 use std::thread::sleep;
 use std::time::Duration;
 
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+
 
 // Screen dimensions
 const WIDTH:  u32 = 640;
 const HEIGHT: u32 = 480;
 
-fn main() {
+pub fn run(options: &Options) -> Result<()> {
 
     // Initialize SDL
-    // We'll just unwrap these - See lesson01 for an example of how to properly handle SDL errors
-    let sdl_context = sdl2::init().unwrap();
-    let video = sdl_context.video().unwrap();
+    // The '?' operator does what the matches in lesson01 do: return early with
+    // the error if there is one, otherwise hand back the Ok value.  map_err
+    // converts SDL's error into our own, recording what we were trying to do.
+    let sdl_context = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl_context.video().map_err(|err| Error::init("video subsystem", err))?;
 
     // Create the window
-    let window = video.window("SDL Tutorial 02", WIDTH, HEIGHT)
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
+    let window = options.window(&video, "SDL Tutorial 02", WIDTH, HEIGHT)?;
 
     // Obtain a renderer and context
-    let mut renderer = window.renderer().build().unwrap();
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
 
     // Load the image as a surface - if we can't load the image, we want to know why
-    let image_surface = match Surface::load_bmp(&Path::new("resources/hello_world.bmp")) {
-        Ok(surface) => surface,
-        Err(err)    => panic!("Could not load image: {}", err)
-    };
+    let image_path = Path::new("resources/hello_world.bmp");
+    let image_surface = resources::load_surface(image_path)?;
 
     // At this point, we could do any number of transformations on the surface, and
     // then when we're ready, we convert it to a texture for quick blitting
-    let image_texture = match renderer.create_texture_from_surface(&image_surface) {
-        Ok(tex)  => tex,
-        Err(err) => panic!("Could not convert image to texture: {}", err)
-    };
+    let image_texture = renderer.create_texture_from_surface(&image_surface)
+        .map_err(Error::texture)?;
 
     // Clear the current window
     renderer.clear();
@@ -47,17 +45,25 @@ fn main() {
     // the entire image to the origin of the window.  If we just wanted to blit a subset
     // of the image, or to a particular section of the window, we would specify Some(rect),
     // where rect is a Rect representing the area to blit from/to.
-    // We match on the result because it could return an error.  Note that we return (), which
-    // is Rust's 'nothing' type.
-    match renderer.copy(&image_texture, None, None) {
-        Ok(()) => (),
-        Err(err) => panic!("Could not render texture: {}", err),
-    };
-    // Flip the screen buffer.
+    // It could fail, in which case the error is passed up to main to be reported.
+    renderer.copy(&image_texture, None, None).map_err(Error::render)?;
+    // Grab the frame for --capture, then flip the screen buffer.
+    let mut capture = options.frame_capture();
+    capture.frame(&renderer)?;
     renderer.present();
 
-    // Pause for two seconds
-    sleep(Duration::new(2, 0));
+    // Pause for two seconds, unless nobody is watching
+    if !capture.is_headless() {
+        sleep(Duration::new(2, 0));
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson02: {}", err);
+        process::exit(1);
+    }
 }
 
     
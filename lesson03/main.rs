@@ -1,4 +1,7 @@
 extern crate sdl2;
+extern crate lazy_foo;
+
+use std::process;
 
 use sdl2::Sdl;
 use sdl2::video::Window;
@@ -6,86 +9,89 @@ use sdl2::render::{Renderer, Texture};
 use sdl2::event::Event;
 use sdl2::surface::{Surface};
 
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+
 const WIDTH:  u32 = 640;
 const HEIGHT: u32 = 480;
 
-const X_IMAGE: &'static str = "resources/x.bmp";
+const X_IMAGE: &str = "resources/x.bmp";
 
 /// Break out initialization into a separate function, which
 /// returns only the Window (we don't need the video context) 
-fn init() -> (Sdl, Window)  {
-    let sdl = sdl2::init().unwrap();
-    let video = sdl.video().unwrap();
+fn init(options: &Options) -> Result<(Sdl, Window)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
     // Create the window
-    let win = match video.window("SDL Tutorial 03", WIDTH, HEIGHT)
-        .position_centered()
-        .opengl()
-        .build() {
-            Ok(window) => window,
-            Err(err)   => panic!("Failed to create Window!: {}", err)
-        };
-    (sdl, win)
+    let win = options.window(&video, "SDL Tutorial 03", WIDTH, HEIGHT)?;
+    Ok((sdl, win))
 }
 
 /// Take a string describing a path and use it to load
 /// an image, and return its surface.
-fn load_image(path: &'static str) -> Surface {
+fn load_image(path: &'static str) -> Result<Surface<'static>> {
     use std::path::Path;
-    match Surface::load_bmp(&Path::new(path)) {
-        Ok(surface) => surface,
-        Err(err)    => panic!("Could not load image: {}", err)
-    }
+    resources::load_surface(Path::new(path))
 }
 
 /// Take a string describing a path and use it to
 /// load an image, and return its texture
-fn load_texture(path: &'static str, renderer: &Renderer) -> Texture {
-    let image = load_image(path);
-    match renderer.create_texture_from_surface(&image) {
-        Ok(tex)    => tex,
-        Err(err)   => panic!("Could not load texture: {}", err)
-    }
+fn load_texture(path: &'static str, renderer: &Renderer) -> Result<Texture> {
+    let image = load_image(path)?;
+    renderer.create_texture_from_surface(&image).map_err(Error::texture)
 }
 
 
-fn main() {
+pub fn run(options: &Options) -> Result<()> {
 
     // Initialize SDL2
-    let (sdl_context, window) = init();
+    let (sdl_context, window) = init(options)?;
     
-    let mut renderer = match window.renderer().build() {
-        Ok(renderer) => renderer,
-        Err(err)     => panic!("Could not obtain renderer: {}", err)
-    };
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
     
     // Load the image
-    let image_texture = load_texture(X_IMAGE, &renderer);
+    let image_texture = load_texture(X_IMAGE, &renderer)?;
 
     // running is 'mut' because we will want to 'flip' it to false when we're ready
     // to exit the game loop.
     let mut running: bool = true;
 
     // Get a handle to the SDL2 event pump
-    let mut event_pump = match sdl_context.event_pump() {
-        Ok(event_pump) => event_pump,
-        Err(err)       => panic!("Could not obtain event pump: {}", err)
-    };
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
     
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
     // game loop
     while running {
         // Extract any pending events from from the event pump and process them
-        for event in event_pump.poll_iter() {
+        for event in events.poll(&mut event_pump) {
             // pattern match on the type of event
-            match event {
-                Event::Quit {..} => {
-                    running = false
-                },
-                _ => {}
+            if let Event::Quit {..} = event {
+                running = false
             }
         }
         // render the texture each pass through the loop
         renderer.clear();
         renderer.copy(&image_texture, None, None).unwrap();
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
         renderer.present();
     }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson03: {}", err);
+        process::exit(1);
+    }
 }
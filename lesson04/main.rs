@@ -1,88 +1,80 @@
 extern crate sdl2;
+extern crate lazy_foo;
 
-use std::collections::HashMap;
+use std::process;
 
 use sdl2::Sdl;
 use sdl2::video::Window;
-use sdl2::render::{Renderer, Texture};
+use sdl2::render::Renderer;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::surface::Surface;
+
+use lazy_foo::assets::Assets;
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
 
 const WIDTH:  u32 = 640;
 const HEIGHT: u32 = 480;
 
+// Which keys do what; see src/input.rs
+const KEY_BINDINGS: &str = "resources/lesson04.keys.ron";
+
 /// Break out initialization into a separate function, which
 /// returns only the Window (we don't need the sdl_context)
-fn init() -> (Sdl, Window)  {
-    let sdl = sdl2::init().unwrap();
-    let video = sdl.video().unwrap();
+fn init(options: &Options) -> Result<(Sdl, Window)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
     // Create the window
-    let win = match video.window("SDL Tutorial 04", WIDTH, HEIGHT)
-        .position_centered()
-        .opengl()
-        .build() {
-            Ok(window) => window,
-            Err(err)   => panic!("Failed to create Window!: {}", err)
-        };
-    (sdl, win)
-}
-
-/// Take a string describing a path and use it to load
-/// an image, and return its surface.
-fn load_image(path: &'static str) -> Surface {
-    use std::path::Path;
-    match Surface::load_bmp(&Path::new(path)) {
-        Ok(surface) => surface,
-        Err(err)    => panic!("Could not load image: {}", err)
-    }
+    let win = options.window(&video, "SDL Tutorial 04", WIDTH, HEIGHT)?;
+    Ok((sdl, win))
 }
 
-/// Take a string describing a path and use it to
-/// load an image, and return its texture
-fn load_texture(path: &'static str, renderer: &Renderer) -> Texture {
-    let image = load_image(path);
-    match renderer.create_texture_from_surface(&image) {
-        Ok(tex)    => tex,
-        Err(err)   => panic!("Could not load texture: {}", err)
+// The image shown for each key, by the name the game loop knows it by
+const IMAGES: [(&str, &str); 5] = [
+    ("up", "resources/up.bmp"),
+    ("down", "resources/down.bmp"),
+    ("left", "resources/left.bmp"),
+    ("right", "resources/right.bmp"),
+    ("press", "resources/press.bmp"),
+];
+
+/// Load the textures we're going to use into an asset cache,
+/// where they can be looked up by name.
+fn load_media(renderer: &Renderer) -> Result<Assets<'static>> {
+    let mut assets = Assets::new();
+    for &(name, path) in IMAGES.iter() {
+        assets.add(name, path);
+        assets.load_texture(renderer, name)?;
     }
-}
-
-/// Load the textures we're going to use into an
-/// easily indexable HashMap.
-fn load_media(renderer: &Renderer) -> HashMap<&'static str, Box<Texture>> {
-    let mut map: HashMap<&'static str, Box<Texture>> = HashMap::new();
-    map.insert("up", Box::new(load_texture("resources/up.bmp", renderer)));
-    map.insert("down", Box::new(load_texture("resources/down.bmp", renderer)));
-    map.insert("left", Box::new(load_texture("resources/left.bmp", renderer)));
-    map.insert("right", Box::new(load_texture("resources/right.bmp", renderer)));
-    map.insert("press", Box::new(load_texture("resources/press.bmp", renderer)));
-    map
+    Ok(assets)
 }
 
 
-fn main() {
+pub fn run(options: &Options) -> Result<()> {
 
     // Initialize SDL2
-    let (sdl_context, window) = init();
+    let (sdl_context, window) = init(options)?;
 
-    let mut renderer = match window.renderer().build() {
-        Ok(renderer) => renderer,
-        Err(err)     => panic!("Could not obtain renderer: {}", err)
-    };
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
 
-    // Load the sprite textures into an hashmap
-    let sprites: HashMap<&'static str, Box<Texture>> = load_media(&renderer);
+    // Load the sprite textures
+    let sprites = load_media(&renderer)?;
 
     // Start up the game loop
     let mut running: bool = true;
     let mut current_image: &str = "press";
 
+    // The actions the keys are bound to
+    let mut input = options.input_map(KEY_BINDINGS)?;
+
     // Obtain the event pump
-    let mut event_pump = match sdl_context.event_pump() {
-        Ok(event_pump) => event_pump,
-        Err(err)       => panic!("Could not obtain event pump: {}", err)
-    };
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
 
     while running {
         // We blit the image to the screen corresponding to the keypress,
@@ -112,32 +104,28 @@ fn main() {
         //         _ => {}
         //     }
         // }
-        for event in event_pump.poll_iter() {
+        // Rather than matching on the keys themselves, we ask the input
+        // map which actions a key is bound to, so the bindings can be
+        // changed without touching the code.
+        for event in events.poll(&mut event_pump) {
+            let actions = input.triggered(&event);
             match event {
                 Event::Quit {..} => {
                     running = false
                 },
-                Event::KeyDown { keycode: k, ..} => match k {
-                    
-                    Some(Keycode::Escape) | Some(Keycode::Q) => {
-                        running = false
-                    },
-                    Some(Keycode::Up) => {
-                        current_image = "up"
-                    },
-                    Some(Keycode::Down) => {
-                        current_image = "down"
-                    },
-                    Some(Keycode::Right) => {
-                        current_image = "right"
-                    },
-                    Some(Keycode::Left) => {
-                        current_image = "left"
-                    },
-                    Some(_) => {
-                        current_image = "press"
-                    },
-                    None => {}
+                Event::KeyDown {..} => {
+                    // Keys that aren't bound to anything show 'press'
+                    current_image = "press";
+                    for action in actions {
+                        match action.as_str() {
+                            "quit" => running = false,
+                            "show_up" => current_image = "up",
+                            "show_down" => current_image = "down",
+                            "show_right" => current_image = "right",
+                            "show_left" => current_image = "left",
+                            _ => {}
+                        }
+                    }
                 },
                 _ => {}
             }
@@ -145,9 +133,22 @@ fn main() {
 
         // Clear and render the currently selected image
         renderer.clear();
-        // sprites[current_image] yields a Box<Texture>, so we use
-        // a '&' to reference it.
-        renderer.copy(&sprites[current_image], None, None).unwrap();
+        // sprites.texture() gives us the cached LTexture; we copy
+        // the SDL texture inside it.
+        renderer.copy(&sprites.texture(current_image)?.texture, None, None).unwrap();
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
         renderer.present();
     }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson04: {}", err);
+        process::exit(1);
+    }
 }
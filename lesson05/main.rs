@@ -1,4 +1,8 @@
 extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
 
 use sdl2::Sdl;
 use sdl2::video::Window;
@@ -7,6 +11,10 @@ use sdl2::event::Event;
 use sdl2::surface::Surface;
 use sdl2::rect::Rect;
 
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+
 const WIDTH:  u32 = 640;
 const HEIGHT: u32 = 480;
 
@@ -21,104 +29,104 @@ const HEIGHT: u32 = 480;
 
 /// Break out initialization into a separate function, which
 /// returns only the Window (we don't need the sdl_context)
-fn init() -> (Sdl, Window)  {
-    let sdl = sdl2::init().unwrap();
-    let video = sdl.video().unwrap();
+fn init(options: &Options) -> Result<(Sdl, Window)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
     // Create the window
-    let win = match video.window("SDL Tutorial 05", WIDTH, HEIGHT)
-        .position_centered()
-        .opengl()
-        .build() {
-            Ok(window) => window,
-            Err(err)   => panic!("Failed to create Window!: {}", err)
-        };
-    (sdl, win)
+    let win = options.window(&video, "SDL Tutorial 05", WIDTH, HEIGHT)?;
+    Ok((sdl, win))
 }
 
 /// Take a string describing a path and use it to load
 /// an image, and return its optimized surface.
-fn load_image(path: &'static str) -> Surface {
+fn load_image(path: &'static str) -> Result<Surface<'static>> {
     use std::path::Path;
-    match Surface::load_bmp(&Path::new(path)) {
-        Ok(surface) => surface,
-        Err(err)    => panic!("Could not load image: {}", err)
-    }
+    resources::load_surface(Path::new(path))
 }
 
 /// Take a string describing a path and use it to
 /// load an image, and return its texture
-fn surface_to_texture(sfc: &Surface, renderer: &Renderer) -> Texture {
-    match renderer.create_texture_from_surface(sfc) {
-        Ok(tex)    => tex,
-        Err(err)   => panic!("Could not load texture: {}", err)
-    }
+fn surface_to_texture(sfc: &Surface, renderer: &Renderer) -> Result<Texture> {
+    renderer.create_texture_from_surface(sfc).map_err(Error::texture)
 }
 
-fn main() {
+pub fn run(options: &Options) -> Result<()> {
 
     // Initialize SDL2
-    let (sdl_context, window) = init();
+    let (sdl_context, window) = init(options)?;
     
     // Get a handle to the SDL2 event pump.  This is done here because we
     // used to need to pass the event pump to a function called 'properties_getters
     // on the window in order to retrieve the window's pixel format.  Thank
     // the gods that rust-sdl doesn't require such shenanigans anymore.
     // We still need the event pump for later, so we keep the line.
-    let mut event_pump = sdl_context.event_pump().unwrap();
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
 
     // Load the image.  Note that we do this after we get the event_pump,
     // because in order to optimize the surface we need the PixelFormat
     // used by the window, which requires a WindowProperties, which can
     // only be obtained by passing an event pump handle to ensure no
     // unsafe operations occur while the event pump is running.
-    let image_surface = load_image("resources/stretch.bmp");
+    let image_path = "resources/stretch.bmp";
+    let image_surface = load_image(image_path)?;
     // Now optimize it, using the pixel format used by the window
     let pixel_format = window.window_pixel_format();
     let sf_pixel_format = image_surface.pixel_format();
-    let optimized_surface = match image_surface
-        .convert(&sf_pixel_format) {
-            Ok(surface) => surface,
-            Err(err)    => panic!("Could not convert surface: {}", err)
-        };
+    let optimized_surface = image_surface
+        .convert(&sf_pixel_format)
+        .map_err(|err| Error::image(Path::new(image_path), err))?;
 
     // Now stretch the optimized surface to the dimensions we want
     let dst_rect = Rect::new(0, 0, WIDTH, HEIGHT);
-    let mut stretched_surface = match Surface::new(WIDTH, HEIGHT, pixel_format) {
-        Ok(surface) => surface,
-        Err(err)      => panic!("Could not create surface: {}", err)
-    };
+    let mut stretched_surface = Surface::new(WIDTH, HEIGHT, pixel_format)
+        .map_err(Error::texture)?;
     // blit_scaled does not return anything, but it does return an SdlResult, so
-    // we unwrap to trigger the panic if we can't blit.
-    optimized_surface.blit_scaled(None, &mut stretched_surface, Some(dst_rect)).unwrap();
+    // we pass any failure on to the caller.
+    optimized_surface.blit_scaled(None, &mut stretched_surface, Some(dst_rect))
+        .map_err(|err| Error::image(Path::new(image_path), err))?;
 
-    let mut renderer = match window.renderer().build() {
-        Ok(renderer) => renderer,
-        Err(err)     => panic!("Could not obtain renderer: {}", err)
-    };
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
  
     // Convert the surface to a texture
-    let image_texture = surface_to_texture(&stretched_surface, &renderer);
+    let image_texture = surface_to_texture(&stretched_surface, &renderer)?;
 
 
     // running is 'mut' because we will want to 'flip' it to false when we're ready
     // to exit the game loop.
     let mut running: bool = true;
    
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
     // game loop
     while running {
         // Extract any pending events from from the event pump and process them
-        for event in event_pump.poll_iter() {
+        for event in events.poll(&mut event_pump) {
             // pattern match on the type of event
-            match event {
-                Event::Quit {..} => {
-                    running = false
-                },
-                _ => {}
+            if let Event::Quit {..} = event {
+                running = false
             }
         }
         // Clear and render the texture each pass through the loop
         renderer.clear();
         renderer.copy(&image_texture, None, None).unwrap();
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
         renderer.present();
     }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson05: {}", err);
+        process::exit(1);
+    }
 }
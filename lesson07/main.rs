@@ -1,57 +1,53 @@
 extern crate sdl2;
+extern crate lazy_foo;
 
 use std::path::Path;
+use std::process;
 
 use sdl2::Sdl;
 use sdl2::video::Window;
 use sdl2::event::Event;
 use sdl2::pixels::Color;
 
-use sdl2::image::{LoadTexture, INIT_PNG, Sdl2ImageContext};
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
 
 const WIDTH:  u32 = 640;
 const HEIGHT: u32 = 480;
 
 // Start using Path for filepaths.
-const IMG_NAME: &'static str = "resources/texture.png";
+const IMG_NAME: &str = "resources/texture.png";
 
-// Note that 'renderer.load_texture' makes this example trivial.  See lesson03
+// Note that 'resources::load_texture' makes this example trivial.  See lesson03
 // to show how we can manually load a surface and convert it to a texture.
     
 /// Break out initialization into a separate function, which
 /// returns only the Window (we don't need the sdl_context)
-fn init() -> (Sdl, Window, Sdl2ImageContext)  {
-    let sdl = sdl2::init().unwrap();
-    let video = sdl.video().unwrap();
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
     // Create the window
-    let win = match video.window("SDL Tutorial 07", WIDTH, HEIGHT)
-        .position_centered()
-        .opengl()
-        .build() {
-            Ok(window) => window,
-            Err(err)   => panic!("Failed to create Window!: {}", err)
-        };
-    let image = sdl2::image::init(INIT_PNG).unwrap();
+    let win = options.window(&video, "SDL Tutorial 07", WIDTH, HEIGHT)?;
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
     
-    (sdl, win, image)
+    Ok((sdl, win, image))
 }
 
 
-fn main() {
+pub fn run(options: &Options) -> Result<()> {
 
     // Initialize SDL2
-    let (sdl_context, window, _image) = init();
+    let (sdl_context, window, _image) = init(options)?;
     
-    let mut renderer = match window.renderer().build() {
-        Ok(renderer) => renderer,
-        Err(err)     => panic!("Could not obtain renderer: {}", err)
-    };
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
 
     // Load the image
-    let image_texture = match renderer.load_texture(Path::new(IMG_NAME)) {
-        Ok(texture) => texture,
-        Err(err)    => panic!("Could not load texture: {}", err)
-    };
+    let image_path = Path::new(IMG_NAME);
+    let image_texture = resources::load_texture(&renderer, image_path)?;
             
     // Set renderer color using the context
     renderer.set_draw_color(Color::RGB(0, 0, 0));
@@ -59,23 +55,40 @@ fn main() {
     let mut running: bool = true;
 
     // Get a handle to the SDL2 event pump
-    let mut event_pump = sdl_context.event_pump().unwrap();
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
     
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
     // game loop
     while running {
         // Extract any pending events from from the event pump and process them
-        for event in event_pump.poll_iter() {
+        for event in events.poll(&mut event_pump) {
             // pattern match on the type of event
-            match event {
-                Event::Quit {..} => {
-                    running = false
-                },
-                _ => {}
+            if let Event::Quit {..} = event {
+                running = false
             }
         }
         // Clear and render the texture each pass through the loop
         renderer.clear();
         renderer.copy(&image_texture, None, None).unwrap();
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
         renderer.present();
     }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson07: {}", err);
+        process::exit(1);
+    }
 }
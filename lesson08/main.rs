@@ -1,6 +1,7 @@
-#![feature(iterator_step_by)]
-#![feature(step_by)]
 extern crate sdl2;
+extern crate lazy_foo;
+
+use std::process;
 
 use sdl2::Sdl;
 use sdl2::video::Window;
@@ -8,53 +9,51 @@ use sdl2::event::Event;
 use sdl2::pixels::Color;
 use sdl2::rect::{Rect, Point};
 
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+
 const WIDTH:  u32 = 640;
 const HEIGHT: u32 = 480;
 
     
 /// Break out initialization into a separate function, which
 /// returns only the Window (we don't need the sdl_context)
-fn init() -> (Sdl, Window)  {
-    let sdl = sdl2::init().unwrap();
-    let video = sdl.video().unwrap();
-    let win = match video.window("SDL Tutorial 08", WIDTH, HEIGHT)
-        .position_centered()
-        .opengl()
-        .build() {
-        Ok(window) => window,
-        Err(err)   => panic!("Failed to create Window!: {}", err)
-    };
-
-    (sdl, win)
+fn init(options: &Options) -> Result<(Sdl, Window)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 08", WIDTH, HEIGHT)?;
+
+    Ok((sdl, win))
 }
 
 
-fn main() {
+pub fn run(options: &Options) -> Result<()> {
 
     // Initialize SDL2
-    let (sdl_context, window) = init();
+    let (sdl_context, window) = init(options)?;
 
     // Set texture filtering to linear
-    let mut renderer = match window.renderer().build() {
-        Ok(renderer) => renderer,
-        Err(err)     => panic!("Could not obtain renderer: {}", err)
-    };
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
 
     let mut running: bool = true;
 
     // Get a handle to the SDL2 event pump
-    let mut event_pump = sdl_context.event_pump().unwrap();
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
     
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
     // game loop
     while running {
         // Extract any pending events from from the event pump and process them
-        for event in event_pump.poll_iter() {
+        for event in events.poll(&mut event_pump) {
             // pattern match on the type of event
-            match event {
-                Event::Quit {..} => {
-                    running = false
-                },
-                _ => {}
+            if let Event::Quit {..} = event {
+                running = false
             }
         }
         
@@ -97,10 +96,24 @@ fn main() {
         // Draw vertical line of yellow dots
         renderer.set_draw_color(Color::RGB(0xff, 0xff, 0));
         for i in (0..HEIGHT as i32).step_by(4) {
-            renderer.draw_point(Point::new(WIDTH as i32 / 2, i as i32)).unwrap();
+            renderer.draw_point(Point::new(WIDTH as i32 / 2, i)).unwrap();
+        }
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
         }
 
         // Update the screen
         renderer.present();
     }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson08: {}", err);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,145 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::keyboard::Scancode;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use lazy_foo::assets::Assets;
+use lazy_foo::error::{Error, Result};
+use lazy_foo::keyboard::KeyboardTracker;
+use lazy_foo::options::Options;
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    // Create the window
+    let win = options.window(&video, "SDL Tutorial 18", WIDTH, HEIGHT)?;
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+
+    Ok((sdl, win, image))
+}
+
+// The image shown for each key, by the name the game loop knows it by
+const IMAGES: [(&str, &str); 5] = [
+    ("up", "resources/up.png"),
+    ("down", "resources/down.png"),
+    ("left", "resources/left.png"),
+    ("right", "resources/right.png"),
+    ("press", "resources/press.png"),
+];
+
+/// Load the textures we're going to use into an asset cache,
+/// where they can be looked up by name.
+fn load_media(renderer: &Renderer) -> Result<Assets<'static>> {
+    let mut assets = Assets::new();
+    for &(name, path) in IMAGES.iter() {
+        assets.add(name, path);
+        assets.load_texture(renderer, name)?;
+    }
+    Ok(assets)
+}
+
+// The arrow keys, and the image shown while each is held
+const ARROWS: [(Scancode, &str); 4] = [
+    (Scancode::Up, "up"),
+    (Scancode::Down, "down"),
+    (Scancode::Left, "left"),
+    (Scancode::Right, "right"),
+];
+
+/// Pick the image to show from the keys that are held down right now,
+/// or 'press' if none of the arrow keys are.
+fn current_image(keys: &KeyboardTracker) -> &'static str {
+    ARROWS.iter()
+        .find(|&&(scancode, _)| keys.held(scancode))
+        .map_or("press", |&(_, image)| image)
+}
+
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image) = init(options)?;
+
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    // Load the sprite textures
+    let sprites = load_media(&renderer)?;
+
+    // Start up the game loop
+    let mut running: bool = true;
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // The keyboard state only says which keys are down now; the tracker
+    // remembers last frame's as well, so it can tell when a key has only
+    // just been pressed.
+    let mut keys = KeyboardTracker::new();
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    while running {
+        // The tracker sees the key events too, so it catches quick taps
+        // and can follow replayed input.
+        for event in events.poll(&mut event_pump) {
+            keys.handle_event(&event);
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        // Instead of using keyboard events to toggle the images, read
+        // SDL's keyboard state - which keys are down right now - and
+        // hand it to the tracker.  Replayed input never gets into the
+        // keyboard state, so then the tracker works it out from the
+        // events it was given instead.
+        if events.is_replaying() {
+            keys.update_from_events();
+        } else {
+            keys.update(&event_pump.keyboard_state());
+        }
+        // Using 'Esc' or 'q' will quit the program.
+        if keys.just_pressed(Scancode::Escape) || keys.just_pressed(Scancode::Q) {
+            running = false;
+        }
+        let current_image = current_image(&keys);
+
+        // Clear and render the currently selected image
+        renderer.clear();
+        // sprites.texture() gives us the cached LTexture; we copy
+        // the SDL texture inside it.
+        renderer.copy(&sprites.texture(current_image)?.texture, None, None)
+            .expect("Could not blit texture to render target!");
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson18: {}", err);
+        process::exit(1);
+    }
+}
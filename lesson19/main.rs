@@ -0,0 +1,262 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::process;
+
+use sdl2::{EventSubsystem, Sdl};
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::controller::Axis;
+use sdl2::keyboard::Scancode;
+use sdl2::pixels::Color;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::gamepad::{Gamepads, Hotplug, DEAD_ZONE};
+use lazy_foo::options::Options;
+use lazy_foo::replay::EventSource;
+use lazy_foo::resources;
+use lazy_foo::texture::LTexture;
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const IMG_ARROW: &str = "resources/arrow.png";
+
+const FONT_FILE: &str = "resources/lazy.ttf";
+const FONT_SIZE: u16 = 20;
+
+const TEXT_COLOR: Color = Color::RGB(0, 0, 0);
+
+// Extra controller mappings, in the community gamecontrollerdb.txt format
+const MAPPINGS: &str = "resources/gamecontrollerdb.txt";
+
+// Which keys and pad buttons do what; see src/input.rs
+const KEY_BINDINGS: &str = "resources/lesson19.keys.ron";
+
+// How much each press of the dead zone keys changes it by
+const DEAD_ZONE_STEP: i16 = 2000;
+
+// The id of the pretend controller the arrow keys drive
+const KEYBOARD_PAD: i32 = 99;
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext, Sdl2TtfContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 19", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+    let ttf = sdl2::ttf::init()
+        .map_err(|err| Error::init("sdl2_ttf", err))?;
+
+    Ok((sdl, win, image, ttf))
+}
+
+/// Render what the pads are up to: how many there are, the last one
+/// to come or go, and the dead zone.
+fn render_status(renderer: &Renderer, font: &Font, gamepads: &Gamepads, last: &str)
+                 -> Result<Vec<LTexture>> {
+    let lines = [
+        format!("Pads: {}   {}", gamepads.len(), last),
+        format!("Dead zone: {} (default {}, [ and ] to change)", gamepads.dead_zone(), DEAD_ZONE),
+    ];
+    lines.iter()
+        .map(|line| LTexture::load_from_rendered_text(renderer, font, line, TEXT_COLOR))
+        .collect()
+}
+
+/// A left stick worked by the arrow keys, for when there's no gamepad.
+/// It doesn't move the arrow itself: it pushes the same axis events a
+/// controller would send onto SDL's event queue, and the arrow follows
+/// those.  They go through the EventSource, which lets them through
+/// even when the keys are being replayed.
+struct KeyboardStick {
+    held: HashSet<Scancode>,
+}
+
+impl KeyboardStick {
+
+    fn new() -> KeyboardStick {
+        KeyboardStick { held: HashSet::new() }
+    }
+
+    // Where the arrow keys held down put the stick
+    fn position(&self) -> (i16, i16) {
+        let axis = |minus, plus| {
+            match (self.held.contains(&minus), self.held.contains(&plus)) {
+                (true, false) => -32768,
+                (false, true) => 32767,
+                _ => 0,
+            }
+        };
+        (axis(Scancode::Left, Scancode::Right), axis(Scancode::Up, Scancode::Down))
+    }
+
+    /// Follow the arrow keys, and push axis events for the pretend
+    /// controller whenever they move the stick.
+    fn handle_event(&mut self, event: &Event, events: &mut EventSource, queue: &EventSubsystem)
+                    -> Result<()> {
+        let before = self.position();
+        match *event {
+            Event::KeyDown { scancode: Some(scancode), repeat: false, .. } => {
+                self.held.insert(scancode);
+            },
+            Event::KeyUp { scancode: Some(scancode), .. } => {
+                self.held.remove(&scancode);
+            },
+            _ => return Ok(()),
+        }
+        let (x, y) = self.position();
+        for &(axis, before, value) in &[(Axis::LeftX, before.0, x), (Axis::LeftY, before.1, y)] {
+            if value != before {
+                events.push(queue, Event::ControllerAxisMotion {
+                    timestamp: 0, which: KEYBOARD_PAD, axis, value,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image, ttf_context) = init(options)?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let arrow = LTexture::new_from_file(&renderer, Path::new(IMG_ARROW))?;
+    let font = resources::load_font(&ttf_context, Path::new(FONT_FILE), FONT_SIZE)?;
+
+    // Open the game controller and joystick subsystems, and teach SDL
+    // about any pads in our mappings file it doesn't already know.
+    // The pads themselves are opened as they're plugged in - including
+    // any that already are, which SDL reports on the first poll.
+    let mut gamepads = Gamepads::new(&sdl_context)?;
+    let mappings = gamepads.load_mappings(Path::new(MAPPINGS))?;
+    let mut last = format!("Loaded {} controller mappings", mappings);
+    let mut status = render_status(&renderer, &font, &gamepads, &last)?;
+
+    // For pushing the keyboard stick's events
+    let event_queue = sdl_context.event()
+        .map_err(|err| Error::init("event subsystem", err))?;
+    let mut keyboard_stick = KeyboardStick::new();
+
+    let mut running: bool = true;
+
+    // The actions the keys and pad buttons are bound to
+    let mut input = options.input_map(KEY_BINDINGS)?;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        let mut changed = false;
+
+        for event in events.poll(&mut event_pump) {
+            // Open and close pads as they come and go
+            match gamepads.handle_event(&event) {
+                Some(Hotplug::Connected(id)) => {
+                    if let Some(pad) = gamepads.pad(id) {
+                        last = format!("Connected {} ({})", pad.name(), id);
+                        changed = true;
+                    }
+                },
+                Some(Hotplug::Disconnected(id)) => {
+                    last = format!("Disconnected pad {}", id);
+                    changed = true;
+                },
+                None => {},
+            }
+            keyboard_stick.handle_event(&event, &mut events, &event_queue)?;
+            for action in input.triggered(&event) {
+                match action.as_str() {
+                    "grow_dead_zone" => {
+                        let dead_zone = gamepads.dead_zone().saturating_add(DEAD_ZONE_STEP);
+                        gamepads.set_dead_zone(dead_zone.min(32000));
+                        changed = true;
+                    },
+                    "shrink_dead_zone" => {
+                        let dead_zone = gamepads.dead_zone() - DEAD_ZONE_STEP;
+                        gamepads.set_dead_zone(dead_zone);
+                        changed = true;
+                    },
+                    "quit" => {
+                        running = false;
+                    },
+                    _ => {},
+                }
+            }
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        if changed {
+            status = render_status(&renderer, &font, &gamepads, &last)?;
+        }
+
+        // Point the arrow the way the first pad's left stick is
+        // pushed.  Left in the dead zone, it goes back to pointing
+        // right, as in the original.
+        let degrees = match gamepads.first().map(|pad| pad.left_stick()) {
+            Some((x, y)) if (x, y) != (0.0, 0.0) => y.atan2(x).to_degrees(),
+            _ => 0.0,
+        };
+
+        // Clear and render the texture each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        // Render the arrow
+        arrow.render_to(&mut renderer,
+                        (WIDTH - arrow.width) as i32 / 2,
+                        (HEIGHT - arrow.height) as i32 / 2,
+                        None,
+                        Some(degrees),
+                        None,
+                        false,
+                        false)?;
+
+        let mut y = 0;
+        for line in &status {
+            line.render(&mut renderer, 0, y, None)?;
+            y += line.height as i32;
+        }
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson19: {}", err);
+        process::exit(1);
+    }
+}
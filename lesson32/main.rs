@@ -0,0 +1,196 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+use lazy_foo::textbox::{TextAction, TextBox};
+use lazy_foo::texture::LTexture;
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const FONT_FILE: &str = "resources/lazy.ttf";
+const FONT_SIZE: u16 = 28;
+
+const PROMPT: &str = "Enter Text:";
+const INITIAL_TEXT: &str = "Some Text";
+
+const TEXT_COLOR: Color = Color::RGB(0, 0, 0);
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext, Sdl2TtfContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 32", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+    let ttf = sdl2::ttf::init()
+        .map_err(|err| Error::init("sdl2_ttf", err))?;
+
+    Ok((sdl, win, image, ttf))
+}
+
+// How wide `text` is when drawn in `font`
+fn text_width(font: &Font, text: &str) -> Result<i32> {
+    font.size_of(text).map(|(width, _)| width as i32).map_err(Error::text)
+}
+
+/// The input text, rendered, and where to draw the cursor and the
+/// underline beneath any composition, relative to its left edge.
+struct RenderedInput {
+    texture: LTexture,
+    cursor_x: i32,
+    underline: Option<(i32, i32)>,
+}
+
+/// Render the text box's contents, composition and all.
+fn render_input(renderer: &Renderer, font: &Font, text: &TextBox) -> Result<RenderedInput> {
+    let display = text.display_text();
+    // SDL_ttf won't render an empty string
+    let texture = if display.is_empty() {
+        LTexture::load_from_rendered_text(renderer, font, " ", TEXT_COLOR)?
+    } else {
+        LTexture::load_from_rendered_text(renderer, font, &display, TEXT_COLOR)?
+    };
+    let cursor_x = text_width(font, &display[..text.display_cursor()])?;
+    let underline = match text.composition_range() {
+        Some(range) => Some((text_width(font, &display[..range.start])?,
+                             text_width(font, &display[..range.end])?)),
+        None => None,
+    };
+    Ok(RenderedInput { texture, cursor_x, underline })
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image, ttf_context) = init(options)?;
+    let video = sdl_context.video().map_err(|err| Error::init("video subsystem", err))?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let font = resources::load_font(&ttf_context, Path::new(FONT_FILE), FONT_SIZE)?;
+    let prompt = LTexture::load_from_rendered_text(&renderer, &font, PROMPT, TEXT_COLOR)?;
+
+    // The text being typed in, and the texture it was last rendered to
+    let mut text = TextBox::with_text(INITIAL_TEXT);
+    let mut input = render_input(&renderer, &font, &text)?;
+    let input_y = prompt.height as i32;
+
+    // Start sending TextInput and TextEditing events.  The rectangle
+    // tells an input method where the text is, so it can put its
+    // list of candidates next to it.
+    let text_input = video.text_input();
+    let clipboard = video.clipboard();
+    text_input.start();
+
+    let mut running: bool = true;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        // Only render the text again when it has changed
+        let mut render_text = false;
+
+        for event in events.poll(&mut event_pump) {
+            match text.handle_event(&event) {
+                Some(TextAction::Changed) => render_text = true,
+                Some(TextAction::Copy) => {
+                    clipboard.set_clipboard_text(text.text())
+                        .unwrap_or_else(|err| eprintln!("Could not copy: {}", err));
+                },
+                Some(TextAction::Paste) => {
+                    match clipboard.clipboard_text() {
+                        Ok(pasted) => {
+                            // Only the first line; this is a one line box
+                            text.insert(pasted.lines().next().unwrap_or(""));
+                            render_text = true;
+                        },
+                        Err(err) => eprintln!("Could not paste: {}", err),
+                    }
+                },
+                None => {},
+            }
+            match event {
+                Event::Quit {..} |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                    running = false
+                },
+                _ => {}
+            }
+        }
+
+        if render_text {
+            input = render_input(&renderer, &font, &text)?;
+        }
+        let input_x = (WIDTH as i32 - input.texture.width as i32) / 2;
+        text_input.set_rect(Rect::new(input_x + input.cursor_x, input_y,
+                                      1, input.texture.height));
+
+        // Clear and render the texture each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        // Render the prompt, and the text below it
+        prompt.render(&mut renderer, (WIDTH - prompt.width) as i32 / 2, 0, None)?;
+        input.texture.render(&mut renderer, input_x, input_y, None)?;
+
+        // Underline any composition, and draw the cursor
+        renderer.set_draw_color(TEXT_COLOR);
+        let bottom = input_y + input.texture.height as i32;
+        if let Some((start, end)) = input.underline {
+            renderer.fill_rect(Rect::new(input_x + start, bottom - 2,
+                                         (end - start).max(1) as u32, 2))
+                .expect("Could not draw the composition's underline");
+        }
+        renderer.fill_rect(Rect::new(input_x + input.cursor_x, input_y,
+                                     2, input.texture.height))
+            .expect("Could not draw the cursor");
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    text_input.stop();
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson32: {}", err);
+        process::exit(1);
+    }
+}
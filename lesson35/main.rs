@@ -0,0 +1,198 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use sdl2::Sdl;
+use sdl2::video::{FullscreenType, Window};
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+use lazy_foo::texture::LTexture;
+use lazy_foo::window::{self, WindowState};
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const TITLE: &str = "SDL Tutorial 35";
+
+const FONT_FILE: &str = "resources/lazy.ttf";
+const FONT_SIZE: u16 = 22;
+
+// Which keys do what; see src/input.rs
+const KEY_BINDINGS: &str = "resources/lesson35.keys.ron";
+
+// Space around the frame drawn just inside the window's edges
+const MARGIN: i32 = 8;
+
+// How long to wait between looks at the event queue while minimized
+const PAUSED_POLL: Duration = Duration::from_millis(10);
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext, Sdl2TtfContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    // Unlike the other lessons' windows, this one can be resized
+    let win = options.resizable_window(&video, TITLE, WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+    let ttf = sdl2::ttf::init()
+        .map_err(|err| Error::init("sdl2_ttf", err))?;
+
+    Ok((sdl, win, image, ttf))
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "On" } else { "Off" }
+}
+
+/// Render a line of text for each thing we know about the window.
+fn render_status(renderer: &Renderer, font: &Font, state: &WindowState,
+                 fullscreen: FullscreenType) -> Result<Vec<LTexture>> {
+    let (width, height) = state.size();
+    let lines = [
+        format!("Window: {} x {}", width, height),
+        format!("Mouse focus: {}   Keyboard focus: {}",
+                on_off(state.has_mouse_focus()), on_off(state.has_keyboard_focus())),
+        format!("Fullscreen: {:?}", fullscreen),
+        "Enter: fullscreen   F11: fullscreen desktop".to_string(),
+    ];
+    lines.iter()
+        .map(|line| LTexture::load_from_rendered_text(renderer, font, line, Color::RGB(0, 0, 0)))
+        .collect()
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image, ttf_context) = init(options)?;
+
+    // Keep track of the window from its events, starting from how it
+    // is now
+    let mut state = WindowState::from_window(&window);
+    let mut fullscreen = window.fullscreen_state();
+
+    // Draw in the window's own pixels rather than scaling a 640x480
+    // picture, so there is something to lay out when it changes size
+    let mut renderer = options.native_renderer(window)?;
+
+    let font = resources::load_font(&ttf_context, Path::new(FONT_FILE), FONT_SIZE)?;
+    let mut status = render_status(&renderer, &font, &state, fullscreen)?;
+    let title = options.title.as_ref().map_or(TITLE, |title| title.as_str());
+
+    let mut running: bool = true;
+
+    // The actions the keys are bound to
+    let mut input = options.input_map(KEY_BINDINGS)?;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        // Whether the status needs rendering again
+        let mut changed = false;
+
+        for event in events.poll(&mut event_pump) {
+            changed |= state.handle_event(&event);
+            for action in input.triggered(&event) {
+                let mode = match action.as_str() {
+                    "fullscreen" => FullscreenType::True,
+                    "fullscreen_desktop" => FullscreenType::Desktop,
+                    "quit" => {
+                        running = false;
+                        continue;
+                    },
+                    _ => continue,
+                };
+                // The window sends SizeChanged once it has switched
+                if let Some(window) = renderer.window_mut() {
+                    fullscreen = window::toggle_fullscreen(window, mode)?;
+                    changed = true;
+                }
+            }
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        // Nothing shows while the window is minimized, so don't draw
+        // anything until it comes back.  The frame still counts, or a
+        // --frames run that gets minimized would never end.
+        if !state.is_visible() {
+            if !capture.frame(&renderer)? {
+                running = false;
+            }
+            thread::sleep(PAUSED_POLL);
+            continue;
+        }
+
+        if changed {
+            status = render_status(&renderer, &font, &state, fullscreen)?;
+            if let Some(window) = renderer.window_mut() {
+                let focus = format!("{} - MouseFocus:{} KeyboardFocus:{}", title,
+                                    on_off(state.has_mouse_focus()),
+                                    on_off(state.has_keyboard_focus()));
+                window.set_title(&focus).map_err(Error::video)?;
+            }
+        }
+
+        // Clear the screen, whatever size it is now
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        // Lay the status out from the window's current size: a frame
+        // just inside the edges, and the lines of text centred in it
+        let (width, height) = state.size();
+        renderer.set_draw_color(Color::RGB(0x00, 0x00, 0xff));
+        if width as i32 > MARGIN * 2 && height as i32 > MARGIN * 2 {
+            renderer.draw_rect(Rect::new(MARGIN, MARGIN,
+                                         width - MARGIN as u32 * 2, height - MARGIN as u32 * 2))
+                .expect("Could not draw the frame");
+        }
+        let total_height: i32 = status.iter().map(|line| line.height as i32).sum();
+        let mut y = (height as i32 - total_height) / 2;
+        for line in &status {
+            line.render(&mut renderer, (width as i32 - line.width as i32) / 2, y, None)?;
+            y += line.height as i32;
+        }
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson35: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,280 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use sdl2::{Sdl, VideoSubsystem};
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use lazy_foo::assets::Assets;
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+use lazy_foo::texture::LTexture;
+use lazy_foo::window::WindowManager;
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+// The inspectors are smaller, and sit to the right of the main view
+const INSPECTOR_WIDTH:  u32 = 320;
+const INSPECTOR_HEIGHT: u32 = 240;
+const INSPECTOR_GAP: i32 = 10;
+
+const FONT_FILE: &str = "resources/lazy.ttf";
+const FONT_SIZE: u16 = 16;
+
+const TEXT_COLOR: Color = Color::RGB(0, 0, 0);
+
+// Which keys do what; see src/input.rs
+const KEY_BINDINGS: &str = "resources/lesson36.keys.ron";
+
+// How many of the latest events the event log shows
+const LOG_LINES: usize = 10;
+
+// How long to wait between looks at the event queue while every
+// window is minimized
+const PAUSED_POLL: Duration = Duration::from_millis(10);
+
+/// Break out initialization into a separate function, which
+/// returns the main window, and the video subsystem to make the others
+fn init(options: &Options)
+        -> Result<(Sdl, VideoSubsystem, Window, Sdl2ImageContext, Sdl2TtfContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 36", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+    let ttf = sdl2::ttf::init()
+        .map_err(|err| Error::init("sdl2_ttf", err))?;
+
+    Ok((sdl, video, win, image, ttf))
+}
+
+/// Make an inspector window at `(x, y)`, and a renderer of its own.
+fn inspector(options: &Options, video: &VideoSubsystem, title: &str, x: i32, y: i32)
+             -> Result<Renderer<'static>> {
+    let window = video.window(title, INSPECTOR_WIDTH, INSPECTOR_HEIGHT)
+        .position(x, y)
+        .build()
+        .map_err(|err| Error::init("window", err))?;
+    options.renderer(window, INSPECTOR_WIDTH, INSPECTOR_HEIGHT)
+}
+
+/// Render each of `lines` with `renderer`; the textures only work with
+/// that renderer's window.
+fn render_lines(renderer: &Renderer, font: &Font, lines: &[String]) -> Result<Vec<LTexture>> {
+    lines.iter()
+        .map(|line| LTexture::load_from_rendered_text(renderer, font, line, TEXT_COLOR))
+        .collect()
+}
+
+/// Draw `lines` one below the other, from `(x, y)` down.
+fn draw_lines(renderer: &mut Renderer, lines: &[LTexture], x: i32, mut y: i32) -> Result<()> {
+    for line in lines {
+        line.render(renderer, x, y, None)?;
+        y += line.height as i32;
+    }
+    Ok(())
+}
+
+/// A line for the event log, for the events worth showing.
+fn describe(event: &Event) -> Option<String> {
+    match *event {
+        Event::Window { ref win_event, .. } => Some(format!("{:?}", win_event)),
+        Event::KeyDown { keycode: Some(keycode), .. } => Some(format!("key {}", keycode.name())),
+        Event::MouseButtonDown { mouse_btn, .. } => Some(format!("{:?} click", mouse_btn)),
+        _ => None,
+    }
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, video, window, _image, ttf_context) = init(options)?;
+    let (x, y) = window.position();
+
+    // Each window gets a renderer, and textures, of its own
+    let mut windows = WindowManager::new();
+    let mut main_assets = Assets::new();
+    main_assets.add("background", "resources/texture.png");
+    let renderer = options.renderer(window, WIDTH, HEIGHT)?;
+    main_assets.load_texture(&renderer, "background")?;
+    let main = windows.add("main", renderer, main_assets);
+
+    let right = x + WIDTH as i32 + INSPECTOR_GAP;
+    let renderer = inspector(options, &video, "Windows", right, y)?;
+    let window_list = windows.add("windows", renderer, Assets::new());
+    let renderer = inspector(options, &video, "Events", right,
+                             y + INSPECTOR_HEIGHT as i32 + INSPECTOR_GAP * 3)?;
+    let event_log = windows.add("events", renderer, Assets::new());
+
+    // Start with the main view in front
+    windows.show(main);
+
+    let font = resources::load_font(&ttf_context, Path::new(FONT_FILE), FONT_SIZE)?;
+    let mut log: VecDeque<String> = VecDeque::new();
+
+    // What each window says, rendered by its own renderer
+    let mut caption = Vec::new();
+    let mut window_lines = Vec::new();
+    let mut log_lines = Vec::new();
+
+    let mut running: bool = true;
+    let mut changed = true;
+
+    // The actions the keys are bound to
+    let mut input = options.input_map(KEY_BINDINGS)?;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the main view's last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        for event in events.poll(&mut event_pump) {
+            // Each window hears about its own events, and hides when
+            // it is closed
+            if let Some(index) = windows.handle_event(&event) {
+                if let (Some(line), Some(window)) = (describe(&event), windows.get(index)) {
+                    log.push_back(format!("{}: {}", window.name(), line));
+                    if log.len() > LOG_LINES {
+                        log.pop_front();
+                    }
+                }
+                changed = true;
+            }
+            for action in input.triggered(&event) {
+                // The number keys bring a window back, and to the front
+                let index = match action.as_str() {
+                    "show_main" => main,
+                    "show_windows" => window_list,
+                    "show_events" => event_log,
+                    "quit" => {
+                        running = false;
+                        continue;
+                    },
+                    _ => continue,
+                };
+                windows.show(index);
+                changed = true;
+            }
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        // Closing the last window ends the lesson
+        if windows.all_hidden() {
+            running = false;
+            continue;
+        }
+        // Nothing shows while they are all minimized.  The frame still
+        // counts, or a --frames run that gets minimized would never end.
+        if !windows.any_visible() {
+            if let Some(window) = windows.get(main) {
+                if !capture.frame(&window.renderer)? {
+                    running = false;
+                }
+            }
+            thread::sleep(PAUSED_POLL);
+            continue;
+        }
+
+        if changed {
+            let focused = windows.focused()
+                .and_then(|index| windows.get(index))
+                .map_or("none", |window| window.name());
+            let hidden: Vec<&str> = windows.iter().enumerate()
+                .filter(|&(index, _)| !windows.is_visible(index))
+                .map(|(_, window)| window.name())
+                .collect();
+            let status = vec![format!("Focus: {}   Hidden: {}", focused,
+                                      if hidden.is_empty() { "none".to_string() }
+                                      else { hidden.join(", ") })];
+
+            let list: Vec<String> = windows.iter().zip(windows.states().iter()).enumerate()
+                .map(|(index, (window, state))| {
+                    let (width, height) = state.size();
+                    format!("{} {}: {}x{}{}{}", index + 1, window.name(), width, height,
+                            if state.is_visible() { "" } else { ", hidden" },
+                            if windows.focused() == Some(index) { ", focus" } else { "" })
+                })
+                .collect();
+            let entries: Vec<String> = if log.is_empty() {
+                vec!["No events yet".to_string()]
+            } else {
+                log.iter().cloned().collect()
+            };
+
+            if let (Some(main_window), Some(list_window), Some(log_window)) =
+                (windows.get(main), windows.get(window_list), windows.get(event_log)) {
+                caption = render_lines(&main_window.renderer, &font, &status)?;
+                window_lines = render_lines(&list_window.renderer, &font, &list)?;
+                log_lines = render_lines(&log_window.renderer, &font, &entries)?;
+            }
+            changed = false;
+        }
+
+        // The main view: the picture, and which window has the focus
+        if let Some(window) = windows.get_mut(main) {
+            let renderer = &mut window.renderer;
+            renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+            renderer.clear();
+            window.assets.texture("background")?.render(renderer, 0, 0, None)?;
+            let strip: u32 = caption.iter().map(|line| line.height).sum();
+            renderer.fill_rect(Rect::new(0, (HEIGHT - strip) as i32, WIDTH, strip))
+                .expect("Could not draw the caption's background");
+            draw_lines(renderer, &caption, 4, (HEIGHT - strip) as i32)?;
+        }
+
+        // The inspectors: every window, and the latest events
+        for &(index, lines) in &[(window_list, &window_lines), (event_log, &log_lines)] {
+            if let Some(window) = windows.get_mut(index) {
+                window.renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+                window.renderer.clear();
+                draw_lines(&mut window.renderer, lines, 4, 4)?;
+            }
+        }
+
+        // Stop once --frames frames have been drawn
+        if let Some(window) = windows.get(main) {
+            if !capture.frame(&window.renderer)? {
+                running = false;
+            }
+        }
+
+        // Update the screens that can be seen
+        for window in windows.visible_mut() {
+            window.renderer.present();
+        }
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson36: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,177 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::{Sdl, VideoSubsystem};
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::pixels::Color;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use lazy_foo::display::{self, Display, ModeName};
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+use lazy_foo::texture::LTexture;
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const FONT_FILE: &str = "resources/lazy.ttf";
+const FONT_SIZE: u16 = 18;
+
+const TEXT_COLOR: Color = Color::RGB(0, 0, 0);
+
+// Which keys do what; see src/input.rs
+const KEY_BINDINGS: &str = "resources/lesson37.keys.ron";
+
+// How many of a display's modes fit in the window
+const MAX_MODES: usize = 14;
+
+/// Break out initialization into a separate function, which
+/// returns the window, and the video subsystem to ask about displays
+fn init(options: &Options)
+        -> Result<(Sdl, VideoSubsystem, Window, Sdl2ImageContext, Sdl2TtfContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 37", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+    let ttf = sdl2::ttf::init()
+        .map_err(|err| Error::init("sdl2_ttf", err))?;
+
+    Ok((sdl, video, win, image, ttf))
+}
+
+/// Render a line of text for each thing SDL says about `display`.
+fn render_display(renderer: &Renderer, font: &Font, display: &Display, count: usize)
+                  -> Result<Vec<LTexture>> {
+    let bounds = display.bounds;
+    let mut lines = vec![
+        format!("Display {} of {}: {}", display.index + 1, count, display.name),
+        format!("Bounds: {}x{} at ({}, {})",
+                bounds.width(), bounds.height(), bounds.x(), bounds.y()),
+        format!("Current mode: {}", ModeName(&display.current)),
+        format!("Desktop mode: {}", ModeName(&display.desktop)),
+        format!("{} modes:", display.modes.len()),
+    ];
+    lines.extend(display.modes.iter()
+                 .take(MAX_MODES)
+                 .map(|mode| format!("    {}", ModeName(mode))));
+    if display.modes.len() > MAX_MODES {
+        lines.push(format!("    ... and {} more", display.modes.len() - MAX_MODES));
+    }
+    lines.push("Up/Down: move to another display".to_string());
+    lines.iter()
+        .map(|line| LTexture::load_from_rendered_text(renderer, font, line, TEXT_COLOR))
+        .collect()
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, video, window, _image, ttf_context) = init(options)?;
+
+    // Everything SDL knows about the displays, and which one the
+    // window is on
+    let displays = display::displays(&video)?;
+    if displays.is_empty() {
+        return Err(Error::video("no displays found"));
+    }
+    let mut current = window.display_index().map_err(Error::video)? as usize;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let font = resources::load_font(&ttf_context, Path::new(FONT_FILE), FONT_SIZE)?;
+    let mut text = render_display(&renderer, &font, &displays[current], displays.len())?;
+
+    let mut running: bool = true;
+
+    // The actions the keys are bound to
+    let mut input = options.input_map(KEY_BINDINGS)?;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        let was_on = current;
+
+        for event in events.poll(&mut event_pump) {
+            for action in input.triggered(&event) {
+                let next = match action.as_str() {
+                    "next_display" => (current + 1) % displays.len(),
+                    "previous_display" => (current + displays.len() - 1) % displays.len(),
+                    "quit" => {
+                        running = false;
+                        continue;
+                    },
+                    _ => continue,
+                };
+                if let Some(window) = renderer.window_mut() {
+                    display::move_to_display(window, &displays[next]);
+                    current = next;
+                }
+            }
+            match event {
+                Event::Quit {..} => {
+                    running = false
+                },
+                // The window may have been dragged onto another display
+                Event::Window { win_event: WindowEvent::Moved(..), .. } => {
+                    if let Some(index) = renderer.window()
+                        .and_then(|window| window.display_index().ok()) {
+                        current = (index as usize).min(displays.len() - 1);
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        if current != was_on {
+            text = render_display(&renderer, &font, &displays[current], displays.len())?;
+        }
+
+        // Clear and render the texture each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        let mut y = 8;
+        for line in &text {
+            line.render(&mut renderer, 8, y, None)?;
+            y += line.height as i32;
+        }
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson37: {}", err);
+        process::exit(1);
+    }
+}
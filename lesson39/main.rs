@@ -0,0 +1,168 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::texture::LTexture;
+use lazy_foo::tiles::{Level, TileMap};
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const DOT_IMG: &str = "resources/dot.png";
+const LEVEL_FILE: &str = "resources/lazy_map.ron";
+
+// The dimensions of the dot, and how many pixels it moves each frame
+// while an arrow is held
+const DOT_WIDTH: u32 = 20;
+const DOT_HEIGHT: u32 = 20;
+const DOT_VEL: i32 = 10;
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 39", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+
+    Ok((sdl, win, image))
+}
+
+/// The dot that moves around the level, bumping into walls.
+struct Dot {
+    x: i32,
+    y: i32,
+    vel_x: i32,
+    vel_y: i32,
+}
+
+impl Dot {
+    fn new() -> Dot {
+        Dot { x: 0, y: 0, vel_x: 0, vel_y: 0 }
+    }
+
+    fn collider(&self) -> Rect {
+        Rect::new(self.x, self.y, DOT_WIDTH, DOT_HEIGHT)
+    }
+
+    /// Speed up when an arrow goes down, and slow down when it comes up.
+    fn handle_event(&mut self, event: &Event) {
+        let (keycode, change) = match *event {
+            Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => (keycode, DOT_VEL),
+            Event::KeyUp { keycode: Some(keycode), repeat: false, .. } => (keycode, -DOT_VEL),
+            _ => return,
+        };
+        match keycode {
+            Keycode::Up => self.vel_y -= change,
+            Keycode::Down => self.vel_y += change,
+            Keycode::Left => self.vel_x -= change,
+            Keycode::Right => self.vel_x += change,
+            _ => {},
+        }
+    }
+
+    /// Move one way then the other, backing off from anything solid.
+    fn move_in(&mut self, map: &TileMap) {
+        self.x += self.vel_x;
+        if map.touches_solid(self.collider()) {
+            self.x -= self.vel_x;
+        }
+        self.y += self.vel_y;
+        if map.touches_solid(self.collider()) {
+            self.y -= self.vel_y;
+        }
+    }
+
+    /// The screen's worth of the level centred on the dot, kept inside
+    /// the level.
+    fn camera(&self, map: &TileMap) -> Rect {
+        let x = self.x + DOT_WIDTH as i32 / 2 - WIDTH as i32 / 2;
+        let y = self.y + DOT_HEIGHT as i32 / 2 - HEIGHT as i32 / 2;
+        Rect::new(x.clamp(0, (map.width() - WIDTH) as i32),
+                  y.clamp(0, (map.height() - HEIGHT) as i32),
+                  WIDTH, HEIGHT)
+    }
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image) = init(options)?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let dot_texture = LTexture::new_from_file(&renderer, Path::new(DOT_IMG))?;
+    let level = Level::load(&renderer, Path::new(LEVEL_FILE))?;
+    if level.map.width() < WIDTH || level.map.height() < HEIGHT {
+        return Err(Error::data(Path::new(LEVEL_FILE), "the level is smaller than the screen"));
+    }
+
+    let mut dot = Dot::new();
+
+    let mut running: bool = true;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        for event in events.poll(&mut event_pump) {
+            match event {
+                Event::Quit {..} => {
+                    running = false
+                },
+                _ => dot.handle_event(&event),
+            }
+        }
+
+        // Move the dot, and the camera after it
+        dot.move_in(&level.map);
+        let camera = dot.camera(&level.map);
+
+        // Clear and render the level and the dot each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+        level.render(&mut renderer, camera)?;
+        dot_texture.render(&mut renderer, dot.x - camera.x(), dot.y - camera.y(), None)?;
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson39: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,111 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::texture::{LTexture, PixelBuffer, Rgba};
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const FOO_IMG: &str = "resources/foo.png";
+
+// The color foo's background is painted in, and what to turn it into:
+// white, but completely transparent
+const COLOR_KEY: Rgba = [0x00, 0xff, 0xff, 0xff];
+const TRANSPARENT: Rgba = [0xff, 0xff, 0xff, 0x00];
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 40", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+
+    Ok((sdl, win, image))
+}
+
+// LoadMedia function
+//
+// LTexture::new_from_file has SDL color key the image; here the
+// pixels are converted to RGBA and the key color replaced by hand.
+// Returns foo as loaded, and foo keyed.
+fn load_media(renderer: &Renderer) -> Result<(LTexture, LTexture)> {
+    let mut pixels = PixelBuffer::load(Path::new(FOO_IMG))?;
+    let before = LTexture::from_pixels(renderer, &pixels)?;
+    pixels.replace(COLOR_KEY, TRANSPARENT);
+    let after = LTexture::from_pixels(renderer, &pixels)?;
+    Ok((before, after))
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image) = init(options)?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let (unkeyed, keyed) = load_media(&renderer)?;
+
+    let mut running: bool = true;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        for event in events.poll(&mut event_pump) {
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        // Clear and render the texture each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        // Foo before keying on the left, and after on the right
+        let y = (HEIGHT - keyed.height) as i32 / 2;
+        unkeyed.render(&mut renderer, (WIDTH / 4 - unkeyed.width / 2) as i32, y, None)?;
+        keyed.render(&mut renderer, (WIDTH * 3 / 4 - keyed.width / 2) as i32, y, None)?;
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson40: {}", err);
+        process::exit(1);
+    }
+}
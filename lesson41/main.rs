@@ -0,0 +1,105 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use lazy_foo::bitmap_font::BitmapFont;
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+// The same characters, as a grid of 16x16 cells and as a BMFont file
+const FONT_IMG: &str = "resources/lazyfont.png";
+const FONT_FNT: &str = "resources/lazyfont.fnt";
+const FONT_COLUMNS: u32 = 16;
+const FONT_ROWS: u32 = 16;
+
+const TEXT: &str = "Bitmap Fonts:\nABCDEFGHIJKLMNOPQRSTUVWXYZ\n\
+                            abcdefghijklmnopqrstuvwxyz\n0123456789";
+const FNT_TEXT: &str = "Loaded from a .fnt file,\nwith kerning: AVAVATo LT";
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 41", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+
+    Ok((sdl, win, image))
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image) = init(options)?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let grid_font = BitmapFont::from_grid(&renderer, Path::new(FONT_IMG), FONT_COLUMNS, FONT_ROWS)?;
+    let fnt_font = BitmapFont::load_fnt(&renderer, Path::new(FONT_FNT))?;
+
+    let mut running: bool = true;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        for event in events.poll(&mut event_pump) {
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        // Clear and render the text each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        // The grid font's text centred in the top half of the screen,
+        // and the .fnt font's in the bottom half
+        let (width, height) = grid_font.glyphs.size(TEXT);
+        grid_font.render(&mut renderer, (WIDTH - width) as i32 / 2,
+                         (HEIGHT / 2 - height) as i32 / 2, TEXT)?;
+        let (width, height) = fnt_font.glyphs.size(FNT_TEXT);
+        fnt_font.render(&mut renderer, (WIDTH - width) as i32 / 2,
+                        (HEIGHT * 3 / 2 - height) as i32 / 2, FNT_TEXT)?;
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson41: {}", err);
+        process::exit(1);
+    }
+}
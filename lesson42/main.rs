@@ -0,0 +1,144 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+use lazy_foo::sprites::{self, Descriptor};
+use lazy_foo::texture::{PixelBuffer, StreamingTexture};
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const FOO_IMG: &str = "resources/foo2.png";
+
+// How many times through the loop each walking frame is shown for
+const FRAMES_PER_IMAGE: usize = 4;
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 42", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+
+    Ok((sdl, win, image))
+}
+
+/// Stands in for Lazy Foo's `DataStream`: somewhere pixels come from
+/// a frame at a time, like a video or an emulator.  Here it is foo's
+/// walking frames, cut out of the sprite sheet into separate buffers.
+struct DataStream {
+    images: Vec<PixelBuffer>,
+    frame: usize,
+}
+
+impl DataStream {
+
+    fn load(path: &Path) -> Result<DataStream> {
+        let sheet = PixelBuffer::load(path)?;
+        let data_path = sprites::descriptor_path(path);
+        let text = resources::read_to_string(&data_path)?;
+        let mut clips = Descriptor::parse(&text)
+            .and_then(|descriptor| descriptor.frames(sheet.width, sheet.height))
+            .map_err(|err| Error::data(&data_path, err))?;
+        let walk = clips.remove("walk")
+            .ok_or_else(|| Error::data(&data_path, "no 'walk' clip"))?;
+        let images = walk.into_iter()
+            .map(|clip| sheet.crop(clip).ok_or_else(|| {
+                Error::data(&data_path, format!("clip {:?} is not inside the {}x{} image",
+                                                clip, sheet.width, sheet.height))
+            }))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(DataStream { images, frame: 0 })
+    }
+
+    fn width(&self) -> u32 {
+        self.images[0].width
+    }
+
+    fn height(&self) -> u32 {
+        self.images[0].height
+    }
+
+    /// The next frame's pixels.
+    fn next(&mut self) -> &PixelBuffer {
+        let image = &self.images[self.frame / FRAMES_PER_IMAGE % self.images.len()];
+        self.frame += 1;
+        image
+    }
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image) = init(options)?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    // Where the pixels come from, and a texture to copy them into
+    let mut stream = DataStream::load(Path::new(FOO_IMG))?;
+    let mut texture = StreamingTexture::new(&renderer, stream.width(), stream.height())?;
+
+    let mut running: bool = true;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        for event in events.poll(&mut event_pump) {
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        // Copy the stream's latest pixels into the texture
+        texture.update(stream.next())?;
+
+        // Clear and render the texture each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+        texture.render(&mut renderer,
+                       (WIDTH - texture.width) as i32 / 2,
+                       (HEIGHT - texture.height) as i32 / 2)?;
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson42: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,128 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::texture::LTexture;
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+// How far the scene turns each frame, in degrees
+const SPIN: f64 = 2.0;
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 43", WIDTH, HEIGHT)?;
+    Ok((sdl, win))
+}
+
+/// Lesson 08's shapes, drawn into whatever the renderer is pointed at.
+fn draw_scene(renderer: &mut Renderer) {
+    renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+    renderer.clear();
+
+    // Red filled quad
+    renderer.set_draw_color(Color::RGB(0xff, 0x00, 0x00));
+    renderer.fill_rect(Rect::new(WIDTH as i32 / 4, HEIGHT as i32 / 4, WIDTH / 2, HEIGHT / 2))
+        .expect("Could not draw the filled quad");
+
+    // Green outlined quad
+    renderer.set_draw_color(Color::RGB(0x00, 0xff, 0x00));
+    renderer.draw_rect(Rect::new(WIDTH as i32 / 6, HEIGHT as i32 / 6,
+                                 WIDTH * 2 / 3, HEIGHT * 2 / 3))
+        .expect("Could not draw the outlined quad");
+
+    // Blue horizontal line
+    renderer.set_draw_color(Color::RGB(0x00, 0x00, 0xff));
+    renderer.draw_line(Point::new(0, HEIGHT as i32 / 2),
+                       Point::new(WIDTH as i32, HEIGHT as i32 / 2))
+        .expect("Could not draw the line");
+
+    // Vertical line of yellow dots
+    renderer.set_draw_color(Color::RGB(0xff, 0xff, 0x00));
+    for i in (0..HEIGHT as i32).filter(|i| i % 4 == 0) {
+        renderer.draw_point(Point::new(WIDTH as i32 / 2, i))
+            .expect("Could not draw a dot");
+    }
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window) = init(options)?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    // A blank texture the size of the screen, to draw the scene into.
+    // Lazy Foo draws it again every frame; it never changes, so here
+    // it is drawn once and the texture reused.
+    let mut target = LTexture::new_target(&renderer, WIDTH, HEIGHT)?;
+    target.draw_into(&mut renderer, draw_scene)?;
+
+    // How far the scene has turned
+    let mut angle: f64 = 0.0;
+
+    let mut running: bool = true;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        for event in events.poll(&mut event_pump) {
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        // Turn the scene a little more each frame
+        angle += SPIN;
+        if angle > 360.0 {
+            angle -= 360.0;
+        }
+
+        // Clear the screen, then draw the scene texture turned about
+        // its centre
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+        target.render_to(&mut renderer, 0, 0, None, Some(angle), None, false, false)?;
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson43: {}", err);
+        process::exit(1);
+    }
+}
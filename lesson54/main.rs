@@ -0,0 +1,185 @@
+extern crate sdl2;
+extern crate lazy_foo;
+
+use std::path::Path;
+use std::process;
+
+use sdl2::Sdl;
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use sdl2::image::{INIT_PNG, Sdl2ImageContext};
+
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use lazy_foo::error::{Error, Result};
+use lazy_foo::options::Options;
+use lazy_foo::resources;
+use lazy_foo::texture::LTexture;
+use lazy_foo::touch::{MouseTouch, Touches};
+
+const WIDTH:  u32 = 640;
+const HEIGHT: u32 = 480;
+
+const FONT_FILE: &str = "resources/lazy.ttf";
+const FONT_SIZE: u16 = 20;
+
+const TEXT_COLOR: Color = Color::RGB(0, 0, 0);
+
+// Which keys do what; see src/input.rs
+const KEY_BINDINGS: &str = "resources/lesson54.keys.ron";
+
+// How big a finger is drawn at full pressure, and at none
+const FINGER_SIZE: f32 = 60.0;
+const MIN_FINGER_SIZE: f32 = 20.0;
+
+// Each finger gets the next of these
+const FINGER_COLORS: [Color; 4] = [
+    Color::RGB(0xff, 0x00, 0x00),
+    Color::RGB(0x00, 0xa0, 0x00),
+    Color::RGB(0x00, 0x00, 0xff),
+    Color::RGB(0xff, 0x80, 0x00),
+];
+
+/// Break out initialization into a separate function, which
+/// returns only the Window (we don't need the sdl_context)
+fn init(options: &Options) -> Result<(Sdl, Window, Sdl2ImageContext, Sdl2TtfContext)> {
+    let sdl = sdl2::init().map_err(|err| Error::init("SDL", err))?;
+    let video = sdl.video().map_err(|err| Error::init("video subsystem", err))?;
+    let win = options.window(&video, "SDL Tutorial 54", WIDTH, HEIGHT)?;
+
+    let image = sdl2::image::init(INIT_PNG)
+        .map_err(|err| Error::init("sdl2_image", err))?;
+    let ttf = sdl2::ttf::init()
+        .map_err(|err| Error::init("sdl2_ttf", err))?;
+
+    Ok((sdl, win, image, ttf))
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "On" } else { "Off" }
+}
+
+/// Render what is going on: how many fingers are down, what the last
+/// of them did, and whether the mouse is standing in for them.
+fn render_status(renderer: &Renderer, font: &Font, touches: &Touches, last: &str,
+                 emulating: bool) -> Result<Vec<LTexture>> {
+    let lines = [
+        format!("Fingers down: {}   Last: {}", touches.len(), last),
+        format!("Mouse as touch: {} (M)", on_off(emulating)),
+    ];
+    lines.iter()
+        .map(|line| LTexture::load_from_rendered_text(renderer, font, line, TEXT_COLOR))
+        .collect()
+}
+
+pub fn run(options: &Options) -> Result<()> {
+
+    // Initialize SDL2
+    let (sdl_context, window, _image, ttf_context) = init(options)?;
+
+    // obtain the renderer
+    let mut renderer = options.renderer(window, WIDTH, HEIGHT)?;
+
+    let font = resources::load_font(&ttf_context, Path::new(FONT_FILE), FONT_SIZE)?;
+
+    // The fingers that are down, and the mouse pretending to be one
+    let mut touches = Touches::new();
+    let mut mouse = MouseTouch::new(WIDTH, HEIGHT);
+    let mut last = "nothing";
+    let mut status = render_status(&renderer, &font, &touches, last, mouse.is_enabled())?;
+
+    let mut running: bool = true;
+
+    // The actions the keys are bound to
+    let mut input = options.input_map(KEY_BINDINGS)?;
+
+    // Get a handle to the SDL2 event pump
+    let mut event_pump = sdl_context.event_pump()
+        .map_err(|err| Error::init("event pump", err))?;
+
+    // Count frames (and capture the last one) for --frames
+    let mut capture = options.frame_capture();
+
+    // Live input, or input saved with --record or played back with --replay
+    let mut events = options.event_source()?;
+
+    // game loop
+    while running {
+        let mut changed = false;
+
+        for event in events.poll(&mut event_pump) {
+            // Real touches, and any the mouse makes
+            let mut fingers = vec![event.clone()];
+            fingers.extend(mouse.translate(&event));
+            for action in input.triggered(&event) {
+                match action.as_str() {
+                    "toggle_emulation" => {
+                        let enabled = !mouse.is_enabled();
+                        fingers.extend(mouse.set_enabled(enabled));
+                        changed = true;
+                    },
+                    "quit" => running = false,
+                    _ => {},
+                }
+            }
+            for finger in &fingers {
+                if touches.handle_event(finger) {
+                    last = match *finger {
+                        Event::FingerDown { .. } => "down",
+                        Event::FingerMotion { .. } => "motion",
+                        _ => "up",
+                    };
+                    changed = true;
+                }
+            }
+            if let Event::Quit {..} = event {
+                running = false
+            }
+        }
+
+        if changed {
+            status = render_status(&renderer, &font, &touches, last, mouse.is_enabled())?;
+        }
+
+        // Clear and render the texture each pass through the loop
+        renderer.set_draw_color(Color::RGB(0xff, 0xff, 0xff));
+        renderer.clear();
+
+        // A square under each finger, bigger the harder it presses
+        for (index, touch) in touches.iter().enumerate() {
+            let size = MIN_FINGER_SIZE + (FINGER_SIZE - MIN_FINGER_SIZE) * touch.pressure;
+            let x = touch.x * WIDTH as f32 - size / 2.0;
+            let y = touch.y * HEIGHT as f32 - size / 2.0;
+            renderer.set_draw_color(FINGER_COLORS[index % FINGER_COLORS.len()]);
+            renderer.fill_rect(Rect::new(x as i32, y as i32, size as u32, size as u32))
+                .expect("Could not draw a finger");
+        }
+
+        let mut y = 0;
+        for line in &status {
+            line.render(&mut renderer, 0, y, None)?;
+            y += line.height as i32;
+        }
+
+        // Stop once --frames frames have been drawn
+        if !capture.frame(&renderer)? {
+            running = false;
+        }
+
+        // Update the screen
+        renderer.present();
+    }
+    // Save the input, if it is being recorded
+    events.finish()
+}
+
+fn main() {
+    if let Err(err) = Options::from_args().and_then(|options| run(&options)) {
+        eprintln!("lesson54: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,272 @@
+//! Sprite animations that advance with time rather than once per frame.
+//!
+//! Stepping to the next clip on every pass through the game loop ties
+//! the animation's speed to the frame rate, and slowing it down with a
+//! `sleep` stalls event handling along with it.  An `Animation` is
+//! instead told how much time has passed each frame, and shows each
+//! clip for as long as that clip's duration says.
+
+use std::time::Duration;
+
+use sdl2::rect::Rect;
+
+/// What happens at the last frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Playback {
+    /// Start again from the first frame
+    Loop,
+    /// Play backwards to the first frame, then forwards again
+    PingPong,
+    /// Stop on the last frame
+    Once,
+}
+
+/// A sequence of clips, each shown for its own length of time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    frames: Vec<(Rect, Duration)>,
+    playback: Playback,
+    speed: f64,
+    // Index of the frame on screen, and how long it has been there
+    current: usize,
+    elapsed: Duration,
+    // Which way a ping-pong animation is going
+    forward: bool,
+    finished: bool,
+}
+
+impl Animation {
+
+    /// An animation that shows each of `clips` for `frame_time`.
+    pub fn new(clips: &[Rect], frame_time: Duration, playback: Playback) -> Animation {
+        let frames = clips.iter().map(|&clip| (clip, frame_time)).collect();
+        Animation::with_durations(frames, playback)
+    }
+
+    /// An animation with a separate duration for every frame.
+    ///
+    /// Panics if there are no frames, or a frame lasts no time at all.
+    pub fn with_durations(frames: Vec<(Rect, Duration)>, playback: Playback) -> Animation {
+        assert!(!frames.is_empty(), "an animation needs at least one frame");
+        assert!(frames.iter().all(|&(_, duration)| duration > Duration::from_secs(0)),
+                "every frame of an animation must last some time");
+        Animation {
+            frames,
+            playback,
+            speed: 1.0,
+            current: 0,
+            elapsed: Duration::from_secs(0),
+            forward: true,
+            finished: false,
+        }
+    }
+
+    /// How fast the animation plays: 2.0 is double speed, 0.5 half
+    /// speed and 0.0 paused.  Negative speeds, infinity and NaN are
+    /// treated as 0.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = if speed.is_finite() { speed.max(0.0) } else { 0.0 };
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Move the animation on by `dt`, the time since the last update.
+    /// A long enough `dt` skips over frames, so the animation keeps
+    /// time even when the frame rate drops.  However fast it is played,
+    /// time saturates rather than overflowing.
+    pub fn update(&mut self, dt: Duration) {
+        if self.finished {
+            return;
+        }
+        let scaled = Duration::try_from_secs_f64(dt.as_secs_f64() * self.speed)
+            .unwrap_or(Duration::MAX);
+        self.elapsed = self.elapsed.saturating_add(scaled);
+        // Going all the way round a repeating animation ends up where it
+        // started, so skip whole trips rather than stepping through them
+        if let Some(cycle) = self.cycle() {
+            if self.elapsed >= cycle {
+                let left = self.elapsed.as_nanos() % cycle.as_nanos();
+                self.elapsed = Duration::new((left / 1_000_000_000) as u64,
+                                             (left % 1_000_000_000) as u32);
+            }
+        }
+        while self.elapsed >= self.frames[self.current].1 {
+            self.elapsed -= self.frames[self.current].1;
+            if !self.advance() {
+                self.elapsed = Duration::from_secs(0);
+                break;
+            }
+        }
+    }
+
+    // How long one trip round a repeating animation takes, from any
+    // frame back to the same frame going the same way
+    fn cycle(&self) -> Option<Duration> {
+        let total = self.frames.iter()
+            .fold(Duration::from_secs(0), |total, &(_, duration)| total.saturating_add(duration));
+        let (first, last) = (self.frames[0].1, self.frames[self.frames.len() - 1].1);
+        match self.playback {
+            Playback::Loop => Some(total),
+            // Every frame but the two ends is shown on the way back too
+            Playback::PingPong if self.frames.len() > 1 =>
+                Some(total.saturating_add(total - first - last)),
+            Playback::PingPong => Some(total),
+            Playback::Once => None,
+        }
+    }
+
+    // Step to the next frame; false once a play-once animation is over
+    fn advance(&mut self) -> bool {
+        let last = self.frames.len() - 1;
+        match self.playback {
+            Playback::Loop => {
+                self.current = if self.current == last { 0 } else { self.current + 1 };
+            },
+            Playback::Once => {
+                if self.current == last {
+                    self.finished = true;
+                    return false;
+                }
+                self.current += 1;
+            },
+            Playback::PingPong => {
+                if last == 0 {
+                    return true;
+                }
+                if self.forward && self.current == last {
+                    self.forward = false;
+                } else if !self.forward && self.current == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.current += 1;
+                } else {
+                    self.current -= 1;
+                }
+            },
+        }
+        true
+    }
+
+    /// Go back to the first frame, and play again if the animation
+    /// had finished.
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.elapsed = Duration::from_secs(0);
+        self.forward = true;
+        self.finished = false;
+    }
+
+    /// The clip to draw now.
+    pub fn clip(&self) -> Rect {
+        self.frames[self.current].0
+    }
+
+    /// The index of the current frame.
+    pub fn frame(&self) -> usize {
+        self.current
+    }
+
+    /// True once a `Playback::Once` animation has shown its last frame
+    /// for the whole of its duration.  Other animations never finish.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clips(count: i32) -> Vec<Rect> {
+        (0..count).map(|i| Rect::new(i * 10, 0, 10, 10)).collect()
+    }
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    // The frame shown after each of `steps` updates of `dt`
+    fn play(animation: &mut Animation, dt: Duration, steps: usize) -> Vec<usize> {
+        (0..steps).map(|_| { animation.update(dt); animation.frame() }).collect()
+    }
+
+    #[test]
+    fn loops_back_to_the_start() {
+        let mut animation = Animation::new(&clips(3), ms(100), Playback::Loop);
+        assert_eq!(animation.frame(), 0);
+        assert_eq!(play(&mut animation, ms(50), 7), vec![0, 1, 1, 2, 2, 0, 0]);
+        assert_eq!(animation.clip(), Rect::new(0, 0, 10, 10));
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_each_end() {
+        let mut animation = Animation::new(&clips(3), ms(100), Playback::PingPong);
+        assert_eq!(play(&mut animation, ms(100), 6), vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let mut animation = Animation::new(&clips(3), ms(100), Playback::Once);
+        assert_eq!(play(&mut animation, ms(100), 2), vec![1, 2]);
+        assert!(!animation.is_finished());
+        assert_eq!(play(&mut animation, ms(100), 2), vec![2, 2]);
+        assert!(animation.is_finished());
+        animation.reset();
+        assert_eq!(animation.frame(), 0);
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn frames_can_have_their_own_durations() {
+        let frames = clips(2).into_iter().zip(vec![ms(100), ms(300)]).collect();
+        let mut animation = Animation::with_durations(frames, Playback::Loop);
+        assert_eq!(play(&mut animation, ms(100), 5), vec![1, 1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn speed_scales_time() {
+        let mut animation = Animation::new(&clips(4), ms(100), Playback::Loop);
+        animation.set_speed(2.0);
+        assert_eq!(play(&mut animation, ms(50), 3), vec![1, 2, 3]);
+        animation.set_speed(0.0);
+        assert_eq!(play(&mut animation, ms(500), 2), vec![3, 3]);
+        animation.set_speed(-1.0);
+        assert_eq!(animation.speed(), 0.0);
+        animation.set_speed(f64::INFINITY);
+        assert_eq!(animation.speed(), 0.0);
+        animation.set_speed(f64::NAN);
+        assert_eq!(animation.speed(), 0.0);
+    }
+
+    #[test]
+    fn long_updates_skip_frames() {
+        let mut animation = Animation::new(&clips(4), ms(100), Playback::Loop);
+        animation.update(ms(250));
+        assert_eq!(animation.frame(), 2);
+        animation.update(ms(250));
+        assert_eq!(animation.frame(), 1);
+        // Whole trips round are skipped, not stepped through
+        animation.update(Duration::from_secs(1_000_000_000) + ms(150));
+        assert_eq!(animation.frame(), 2);
+
+        let mut animation = Animation::new(&clips(3), ms(100), Playback::PingPong);
+        animation.update(Duration::from_secs(1_000_000_000) + ms(350));
+        assert_eq!(animation.frame(), 1);
+    }
+
+    #[test]
+    fn huge_speeds_saturate() {
+        let mut animation = Animation::new(&clips(3), ms(100), Playback::Loop);
+        animation.set_speed(1e300);
+        animation.update(ms(16));
+        assert!(animation.frame() < 3);
+
+        let mut animation = Animation::new(&clips(3), ms(100), Playback::Once);
+        animation.set_speed(1e300);
+        animation.update(ms(16));
+        assert!(animation.is_finished());
+    }
+}
@@ -0,0 +1,254 @@
+//! A cache of the images and fonts a lesson uses, looked up by name.
+//!
+//! Lessons used to keep their own `HashMap` of textures, filled in by
+//! hand in `load_media`.  `Assets` does the same job for every lesson:
+//! each file is given a logical name with `add`, loaded the first time
+//! it is asked for, and handed out by reference after that.  Assets are
+//! cached by file, so two names for the same PNG share one texture
+//! rather than uploading it twice, even if their paths are spelled
+//! differently (`./resources/x.png` and `resources/x.png`).
+//!
+//! Anything that needs an asset - a scene, a widget, a helper function -
+//! borrows the `Assets` rather than owning its own copy.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use sdl2::render::Renderer;
+use sdl2::surface::Surface;
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use error::{Error, Result};
+use resources;
+use texture::LTexture;
+
+/// Named textures, surfaces and fonts, each loaded at most once.
+///
+/// `'ttf` is the lifetime of the SDL_ttf context the fonts were loaded
+/// with.  An `Assets` that never loads a font can be `Assets<'static>`.
+pub struct Assets<'ttf> {
+    // Logical name -> file
+    paths: HashMap<String, PathBuf>,
+    textures: HashMap<PathBuf, LTexture>,
+    surfaces: HashMap<PathBuf, Surface<'static>>,
+    fonts: HashMap<(PathBuf, u16), Font<'ttf>>,
+}
+
+/// How much the cached assets are holding on to.  Byte counts are
+/// estimates: the pixel data at the size and format SDL reports, not
+/// counting any padding or copies a graphics driver makes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryUsage {
+    pub textures: usize,
+    pub texture_bytes: usize,
+    pub surfaces: usize,
+    pub surface_bytes: usize,
+    pub fonts: usize,
+}
+
+impl<'ttf> Assets<'ttf> {
+
+    pub fn new() -> Assets<'ttf> {
+        Assets {
+            paths: HashMap::new(),
+            textures: HashMap::new(),
+            surfaces: HashMap::new(),
+            fonts: HashMap::new(),
+        }
+    }
+
+    /// Give the file at `path` the logical name `name`.  Nothing is
+    /// loaded until the asset is first asked for.
+    pub fn add<P: AsRef<Path>>(&mut self, name: &str, path: P) {
+        self.paths.insert(name.to_string(), normalize(path.as_ref()));
+    }
+
+    // The file a name refers to
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        self.paths.get(name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))
+    }
+
+    /// Load the image called `name` as a texture, color keyed the same
+    /// way as `LTexture::new_from_file`.  If the file has already been
+    /// loaded, under this name or any other, the cached texture is
+    /// returned instead.
+    pub fn load_texture(&mut self, renderer: &Renderer, name: &str) -> Result<&mut LTexture> {
+        let path = self.path(name)?;
+        if !self.textures.contains_key(&path) {
+            let texture = LTexture::new_from_file(renderer, &path)?;
+            self.textures.insert(path.clone(), texture);
+        }
+        Ok(self.textures.get_mut(&path).unwrap())
+    }
+
+    /// The texture called `name`, which must already have been loaded.
+    pub fn texture(&self, name: &str) -> Result<&LTexture> {
+        self.path(name).ok()
+            .and_then(|path| self.textures.get(&path))
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))
+    }
+
+    /// As `texture`, for changing its color or alpha.  Every name for
+    /// the same file will see the change.
+    pub fn texture_mut(&mut self, name: &str) -> Result<&mut LTexture> {
+        let path = self.path(name)?;
+        self.textures.get_mut(&path)
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))
+    }
+
+    /// Load the image called `name` as a surface, for lessons that
+    /// work with pixels in memory rather than on the GPU.
+    pub fn load_surface(&mut self, name: &str) -> Result<&Surface<'static>> {
+        let path = self.path(name)?;
+        if !self.surfaces.contains_key(&path) {
+            let surface = resources::load_surface(&path)?;
+            self.surfaces.insert(path.clone(), surface);
+        }
+        Ok(&self.surfaces[&path])
+    }
+
+    /// The surface called `name`, which must already have been loaded.
+    pub fn surface(&self, name: &str) -> Result<&Surface<'static>> {
+        self.path(name).ok()
+            .and_then(|path| self.surfaces.get(&path))
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))
+    }
+
+    /// Load the font called `name` at `size` points.  Each size of a
+    /// font is cached separately.
+    pub fn load_font(&mut self, ttf: &'ttf Sdl2TtfContext, name: &str, size: u16)
+                     -> Result<&Font<'ttf>> {
+        let key = (self.path(name)?, size);
+        if !self.fonts.contains_key(&key) {
+            let font = resources::load_font(ttf, &key.0, size)?;
+            self.fonts.insert(key.clone(), font);
+        }
+        Ok(&self.fonts[&key])
+    }
+
+    /// The font called `name` at `size` points, which must already
+    /// have been loaded.
+    pub fn font(&self, name: &str, size: u16) -> Result<&Font<'ttf>> {
+        self.path(name).ok()
+            .and_then(|path| self.fonts.get(&(path, size)))
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))
+    }
+
+    /// Count what is loaded, and estimate how much memory it takes.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let texture_bytes = self.textures.values()
+            .map(|texture| {
+                let query = texture.texture.query();
+                query.format.byte_size_of_pixels(query.width as usize * query.height as usize)
+            })
+            .sum();
+        let surface_bytes = self.surfaces.values()
+            .map(|surface| surface.pitch() as usize * surface.height() as usize)
+            .sum();
+        MemoryUsage {
+            textures: self.textures.len(),
+            texture_bytes,
+            surfaces: self.surfaces.len(),
+            surface_bytes,
+            fonts: self.fonts.len(),
+        }
+    }
+}
+
+// Spell `path` one way, so that it can be a cache key: drop any `.`
+// and fold `..` into the directory before it.  This is done on the
+// text of the path alone, since an embedded resource has no file on
+// disk to ask about.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => match normal.components().next_back() {
+                Some(Component::Normal(_)) => { normal.pop(); },
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {},
+                _ => normal.push(".."),
+            },
+            other => normal.push(other.as_os_str()),
+        }
+    }
+    normal
+}
+
+impl<'ttf> Default for Assets<'ttf> {
+    fn default() -> Assets<'ttf> {
+        Assets::new()
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} textures ({}), {} surfaces ({}), {} fonts",
+               self.textures, Bytes(self.texture_bytes),
+               self.surfaces, Bytes(self.surface_bytes),
+               self.fonts)
+    }
+}
+
+// Formats a byte count in the largest unit that keeps it above 1
+struct Bytes(usize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const KIB: usize = 1024;
+        const MIB: usize = 1024 * KIB;
+        match self.0 {
+            n if n >= MIB => write!(f, "{:.1} MiB", n as f64 / MIB as f64),
+            n if n >= KIB => write!(f, "{:.1} KiB", n as f64 / KIB as f64),
+            n => write!(f, "{} B", n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_names_are_an_error() {
+        let mut assets = Assets::new();
+        assets.add("foo", "resources/foo.png");
+        assert!(assets.texture("bar").is_err());
+        // Added, but not loaded yet
+        assert!(assets.texture("foo").is_err());
+        assert!(assets.surface("foo").is_err());
+    }
+
+    #[test]
+    fn paths_are_cached_however_they_are_spelled() {
+        let mut assets: Assets = Assets::new();
+        assets.add("plain", "resources/x.png");
+        assets.add("dotted", "./resources/x.png");
+        assets.add("around", "resources/../resources/./x.png");
+        assets.add("up", "../x.png");
+        assert_eq!(assets.path("dotted").unwrap(), Path::new("resources/x.png"));
+        assert_eq!(assets.path("around").unwrap(), Path::new("resources/x.png"));
+        assert_eq!(assets.path("up").unwrap(), Path::new("../x.png"));
+    }
+
+    #[test]
+    fn nothing_loaded_uses_no_memory() {
+        let assets: Assets = Assets::new();
+        assert_eq!(assets.memory_usage(), MemoryUsage::default());
+    }
+
+    #[test]
+    fn memory_usage_is_readable() {
+        let usage = MemoryUsage {
+            textures: 5,
+            texture_bytes: 5 * 640 * 480 * 4,
+            surfaces: 1,
+            surface_bytes: 512,
+            fonts: 0,
+        };
+        assert_eq!(usage.to_string(), "5 textures (5.9 MiB), 1 surfaces (512 B), 0 fonts");
+    }
+}
@@ -0,0 +1,442 @@
+//! Bitmap fonts: text drawn from a picture of every character rather
+//! than rendered by SDL_ttf, which keeps pixel art fonts crisp.
+//!
+//! Two kinds of font image are understood.  Lazy Foo's is a grid of
+//! equally sized cells, one per character code in order, on a
+//! transparent background.  How wide each character is comes from
+//! the columns of its cell that have something in them, so narrow
+//! letters like `i` aren't spaced as widely as `W`.
+//!
+//! The other is an [AngelCode BMFont](https://www.angelcode.com/products/bmfont/)
+//! `.fnt` file in its text format, as written by BMFont and most other
+//! bitmap font tools.  It says exactly where each character is on the
+//! image, how to offset it and how far to move on afterwards:
+//!
+//! ```text
+//! common lineHeight=18 base=14 scaleW=256 scaleH=256 pages=1 packed=0
+//! page id=0 file="lazyfont.png"
+//! char id=65 x=34 y=64 width=10 height=16 xoffset=0 yoffset=0 xadvance=12 page=0 chnl=15
+//! kerning first=65 second=86 amount=-2
+//! ```
+//!
+//! Only fonts that fit on one page (one image) are supported.  The
+//! image is looked for next to the `.fnt` file.
+
+use std::char;
+use std::collections::HashMap;
+use std::path::Path;
+
+use sdl2::rect::Rect;
+use sdl2::render::Renderer;
+
+use error::{Error, Result};
+use resources;
+use texture::{LTexture, PixelBuffer};
+
+/// Where one character is on the font image, and how to place it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+    /// None for characters with nothing to draw, like space
+    pub clip: Option<Rect>,
+    /// Where to draw the clip, relative to the pen position
+    pub x_offset: i32,
+    pub y_offset: i32,
+    /// How far to move the pen on afterwards
+    pub advance: i32,
+}
+
+/// Every character in a font, and how to lay out lines of them.  This
+/// is the part of a `BitmapFont` that doesn't need a renderer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyphs {
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), i32>,
+    /// How far apart lines are
+    pub line_height: i32,
+    /// How far a space (or a character the font doesn't have) moves
+    /// the pen
+    pub space: i32,
+}
+
+impl Glyphs {
+
+    /// Slice a grid of `columns` x `rows` cells, one per character
+    /// code starting from 0, trimming each character to the columns
+    /// that aren't completely transparent.  Empty cells are left out.
+    ///
+    /// Rows that no character uses are trimmed off the top and bottom
+    /// of every glyph, so lines are only as far apart as they need to be.
+    pub fn from_grid(image: &PixelBuffer, columns: u32, rows: u32)
+                     -> ::std::result::Result<Glyphs, String> {
+        if columns == 0 || rows == 0 ||
+            !image.width.is_multiple_of(columns) || !image.height.is_multiple_of(rows) {
+            return Err(format!("a {}x{} image can't be split into {}x{} cells",
+                               image.width, image.height, columns, rows));
+        }
+        let (cell_w, cell_h) = (image.width / columns, image.height / rows);
+        let inked = |x: u32, y: u32| image.get(x, y)[3] != 0;
+
+        // The left and right edges of each character, and the highest
+        // and lowest rows any of them reach
+        let mut edges = Vec::new();
+        let (mut top, mut bottom) = (cell_h, 0);
+        for cell in 0..columns * rows {
+            let (cell_x, cell_y) = (cell % columns * cell_w, cell / columns * cell_h);
+            let ink: Vec<u32> = (0..cell_w)
+                .filter(|&x| (0..cell_h).any(|y| inked(cell_x + x, cell_y + y)))
+                .collect();
+            let (left, right) = match (ink.first(), ink.last()) {
+                (Some(&left), Some(&right)) => (left, right),
+                _ => continue,
+            };
+            for y in (0..cell_h).filter(|&y| (left..=right).any(|x| inked(cell_x + x, cell_y + y))) {
+                top = top.min(y);
+                bottom = bottom.max(y);
+            }
+            if let Some(c) = char::from_u32(cell) {
+                edges.push((c, cell_x + left, cell_y, right - left + 1));
+            }
+        }
+        if edges.is_empty() {
+            return Err("there are no characters in the image".to_string());
+        }
+
+        // A pixel of space between characters for every eight of cell,
+        // so a font drawn at twice its size keeps its proportions
+        let spacing = (cell_w / 8).max(1) as i32;
+        let height = bottom - top + 1;
+        let glyphs = edges.into_iter()
+            .map(|(c, x, y, width)| (c, Glyph {
+                clip: Some(Rect::new(x as i32, (y + top) as i32, width, height)),
+                x_offset: 0,
+                y_offset: 0,
+                advance: width as i32 + spacing,
+            }))
+            .collect();
+        Ok(Glyphs {
+            glyphs,
+            kerning: HashMap::new(),
+            line_height: height as i32 + spacing,
+            space: cell_w as i32 / 2,
+        })
+    }
+
+    /// Read a BMFont text-format `.fnt` file, returning its characters
+    /// along with the name of the image they are on.
+    pub fn parse_fnt(text: &str) -> ::std::result::Result<(Glyphs, String), String> {
+        let mut line_height = None;
+        let mut page = None;
+        let mut glyphs = HashMap::new();
+        let mut kerning = HashMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let at_line = |err: String| format!("line {}: {}", number + 1, err);
+            let (tag, fields) = Fields::parse(line).map_err(&at_line)?;
+            match tag {
+                "common" => {
+                    if fields.int("pages").map_err(&at_line)? != 1 {
+                        return Err(at_line("only fonts with one page are supported".to_string()));
+                    }
+                    line_height = Some(fields.int("lineHeight").map_err(&at_line)?);
+                },
+                "page" => {
+                    if fields.int("id").map_err(&at_line)? != 0 {
+                        return Err(at_line("only fonts with one page are supported".to_string()));
+                    }
+                    page = Some(fields.get("file").map_err(&at_line)?.to_string());
+                },
+                "char" => {
+                    let c = fields.character("id").map_err(&at_line)?;
+                    let size = |name| fields.int(name).and_then(|size| {
+                        if size < 0 {
+                            Err(format!("'{}' can't be negative", name))
+                        } else {
+                            Ok(size as u32)
+                        }
+                    });
+                    let (width, height) = (size("width").map_err(&at_line)?,
+                                           size("height").map_err(&at_line)?);
+                    let clip = if width > 0 && height > 0 {
+                        Some(Rect::new(fields.int("x").map_err(&at_line)?,
+                                       fields.int("y").map_err(&at_line)?,
+                                       width, height))
+                    } else {
+                        None
+                    };
+                    let glyph = Glyph {
+                        clip,
+                        x_offset: fields.int("xoffset").map_err(&at_line)?,
+                        y_offset: fields.int("yoffset").map_err(&at_line)?,
+                        advance: fields.int("xadvance").map_err(&at_line)?,
+                    };
+                    glyphs.insert(c, glyph);
+                },
+                "kerning" => {
+                    let pair = (fields.character("first").map_err(&at_line)?,
+                                fields.character("second").map_err(&at_line)?);
+                    kerning.insert(pair, fields.int("amount").map_err(&at_line)?);
+                },
+                // info, chars and kernings only say things we don't
+                // need to know
+                _ => {},
+            }
+        }
+        let line_height = line_height.ok_or("there is no 'common' line")?;
+        let page = page.ok_or("there is no 'page' line")?;
+        if glyphs.is_empty() {
+            return Err("there are no characters in the font".to_string());
+        }
+        let space = glyphs.get(&' ').map_or(line_height / 2, |glyph| glyph.advance);
+        Ok((Glyphs { glyphs, kerning, line_height, space }, page))
+    }
+
+    /// Check that every character lies inside a `width` x `height` image.
+    pub fn check_fits(&self, width: u32, height: u32) -> ::std::result::Result<(), String> {
+        let image = Rect::new(0, 0, width, height);
+        let outside = self.glyphs.iter()
+            .filter_map(|(&c, glyph)| glyph.clip.map(|clip| (c, clip)))
+            .find(|&(_, clip)| image.intersection(clip) != Some(clip));
+        match outside {
+            Some((c, clip)) => Err(format!("character {:?} ({:?}) is not inside the {}x{} image",
+                                           c, clip, width, height)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn get(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c)
+    }
+
+    /// Where each character of `text` comes from on the image, and
+    /// where it goes relative to the top left of the text.  Lines are
+    /// broken at `\n`.
+    pub fn layout(&self, text: &str) -> Vec<(Rect, Rect)> {
+        let mut placed = Vec::new();
+        let (mut x, mut y) = (0, 0);
+        let mut previous = None;
+        for c in text.chars() {
+            if c == '\n' {
+                x = 0;
+                y += self.line_height;
+                previous = None;
+                continue;
+            }
+            if let Some(kern) = previous.and_then(|p| self.kerning.get(&(p, c))) {
+                x += kern;
+            }
+            match self.glyphs.get(&c) {
+                Some(glyph) => {
+                    if let Some(clip) = glyph.clip {
+                        placed.push((clip, Rect::new(x + glyph.x_offset, y + glyph.y_offset,
+                                                     clip.width(), clip.height())));
+                    }
+                    x += glyph.advance;
+                },
+                None => x += self.space,
+            }
+            previous = Some(c);
+        }
+        placed
+    }
+
+    /// How wide the widest line of `text` is, and how tall all of the
+    /// lines are together.
+    pub fn size(&self, text: &str) -> (u32, u32) {
+        let width = text.split('\n')
+            .map(|line| {
+                let mut previous = None;
+                line.chars()
+                    .map(|c| {
+                        let kern = previous.and_then(|p| self.kerning.get(&(p, c))).cloned();
+                        previous = Some(c);
+                        kern.unwrap_or(0) + self.glyphs.get(&c).map_or(self.space, |glyph| glyph.advance)
+                    })
+                    .sum::<i32>()
+            })
+            .max()
+            .unwrap_or(0);
+        let lines = text.split('\n').count() as i32;
+        (width.max(0) as u32, (lines * self.line_height) as u32)
+    }
+}
+
+// The fields of one line of a .fnt file: a tag, then key=value pairs,
+// where values with spaces in are quoted
+struct Fields<'a> {
+    pairs: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Fields<'a> {
+
+    fn parse(line: &'a str) -> ::std::result::Result<(&'a str, Fields<'a>), String> {
+        let line = line.trim();
+        let (tag, mut rest) = line.split_at(line.find(char::is_whitespace).unwrap_or(line.len()));
+        let mut pairs = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            let equals = rest.find('=')
+                .ok_or_else(|| format!("expected key=value, found '{}'", rest))?;
+            let key = &rest[..equals];
+            rest = &rest[equals + 1..];
+            let value = if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted.find('"')
+                    .ok_or_else(|| format!("the value of '{}' has no closing quote", key))?;
+                rest = &quoted[end + 1..];
+                &quoted[..end]
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let value = &rest[..end];
+                rest = &rest[end..];
+                value
+            };
+            pairs.push((key, value));
+        }
+        Ok((tag, Fields { pairs }))
+    }
+
+    fn get(&self, key: &str) -> ::std::result::Result<&'a str, String> {
+        self.pairs.iter()
+            .find(|&&(k, _)| k == key)
+            .map(|&(_, value)| value)
+            .ok_or_else(|| format!("'{}' is missing", key))
+    }
+
+    fn int(&self, key: &str) -> ::std::result::Result<i32, String> {
+        let value = self.get(key)?;
+        value.parse().map_err(|_| format!("'{}' should be a number, not '{}'", key, value))
+    }
+
+    fn character(&self, key: &str) -> ::std::result::Result<char, String> {
+        let code = self.int(key)?;
+        char::from_u32(code as u32)
+            .ok_or_else(|| format!("'{}' is not a character code: {}", key, code))
+    }
+}
+
+/// A font image, and the characters on it.
+pub struct BitmapFont {
+    pub texture: LTexture,
+    pub glyphs: Glyphs,
+}
+
+impl BitmapFont {
+
+    /// Load a Lazy Foo style font: an image split into `columns` x
+    /// `rows` cells, one per character.
+    pub fn from_grid(renderer: &Renderer, path: &Path, columns: u32, rows: u32)
+                     -> Result<BitmapFont> {
+        let image = PixelBuffer::load(path)?;
+        let glyphs = Glyphs::from_grid(&image, columns, rows)
+            .map_err(|err| Error::data(path, err))?;
+        let texture = LTexture::from_pixels(renderer, &image)?;
+        Ok(BitmapFont { texture, glyphs })
+    }
+
+    /// Load a BMFont `.fnt` file, and the image it names.
+    pub fn load_fnt(renderer: &Renderer, path: &Path) -> Result<BitmapFont> {
+        let text = resources::read_to_string(path)?;
+        let (glyphs, page) = Glyphs::parse_fnt(&text)
+            .map_err(|err| Error::data(path, err))?;
+        let image = PixelBuffer::load(&path.with_file_name(page))?;
+        glyphs.check_fits(image.width, image.height)
+            .map_err(|err| Error::data(path, err))?;
+        let texture = LTexture::from_pixels(renderer, &image)?;
+        Ok(BitmapFont { texture, glyphs })
+    }
+
+    /// Draw `text` with its top left corner at (`x`, `y`).
+    pub fn render(&self, renderer: &mut Renderer, x: i32, y: i32, text: &str) -> Result<()> {
+        for (clip, place) in self.glyphs.layout(text) {
+            self.texture.render(renderer, x + place.x(), y + place.y(), Some(clip))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INK: [u8; 4] = [0, 0, 0, 0xff];
+
+    // A 2x1 grid of 8x8 cells: character 0 is a 3 pixel wide bar in
+    // rows 2 to 5, character 1 is empty
+    fn grid() -> PixelBuffer {
+        let mut image = PixelBuffer::new(16, 8).unwrap();
+        for x in 2..5 {
+            for y in 2..6 {
+                image.set(x, y, INK);
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn grid_glyphs_are_trimmed_to_their_ink() {
+        let glyphs = Glyphs::from_grid(&grid(), 2, 1).unwrap();
+        let bar = glyphs.get('\u{0}').unwrap();
+        assert_eq!(bar.clip, Some(Rect::new(2, 2, 3, 4)));
+        assert_eq!(bar.advance, 4);
+        assert_eq!(glyphs.get('\u{1}'), None);
+        assert_eq!(glyphs.line_height, 5);
+        assert_eq!(glyphs.space, 4);
+        assert!(Glyphs::from_grid(&grid(), 3, 1).is_err());
+        assert!(Glyphs::from_grid(&PixelBuffer::new(16, 8).unwrap(), 2, 1).is_err());
+    }
+
+    const FNT: &str = r#"info face="Two Letters" size=8
+common lineHeight=10 base=8 scaleW=32 scaleH=16 pages=1
+page id=0 file="letters.png"
+chars count=3
+char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=3
+char id=65 x=0 y=0 width=5 height=8 xoffset=0 yoffset=1 xadvance=6
+char id=86 x=8 y=0 width=5 height=8 xoffset=1 yoffset=1 xadvance=6
+kerning first=65 second=86 amount=-2
+"#;
+
+    #[test]
+    fn fnt_files_are_parsed() {
+        let (glyphs, page) = Glyphs::parse_fnt(FNT).unwrap();
+        assert_eq!(page, "letters.png");
+        assert_eq!(glyphs.line_height, 10);
+        assert_eq!(glyphs.space, 3);
+        assert_eq!(glyphs.get('V'), Some(&Glyph {
+            clip: Some(Rect::new(8, 0, 5, 8)), x_offset: 1, y_offset: 1, advance: 6,
+        }));
+        assert!(glyphs.check_fits(32, 16).is_ok());
+        assert!(glyphs.check_fits(12, 16).is_err());
+    }
+
+    #[test]
+    fn fnt_errors_name_the_line() {
+        let err = Glyphs::parse_fnt(&FNT.replace("x=8", "x=eight")).unwrap_err();
+        assert!(err.starts_with("line 7:"), "{}", err);
+        let err = Glyphs::parse_fnt(&FNT.replace("pages=1", "pages=2")).unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+        assert!(Glyphs::parse_fnt("page id=0 file=\"letters.png").is_err());
+        assert!(Glyphs::parse_fnt("char id=65").is_err());
+    }
+
+    #[test]
+    fn text_is_laid_out_in_lines() {
+        let (glyphs, _) = Glyphs::parse_fnt(FNT).unwrap();
+        // A then V is kerned closer; the space and the unknown ? both
+        // move the pen on by the width of a space
+        let placed = glyphs.layout("AV\n A?V");
+        let places: Vec<(i32, i32)> = placed.iter().map(|&(_, place)| (place.x(), place.y())).collect();
+        assert_eq!(places, vec![(0, 1), (5, 1), (3, 11), (13, 11)]);
+        assert_eq!(glyphs.size("AV\n A?V"), (18, 20));
+        assert_eq!(glyphs.size(""), (0, 10));
+    }
+
+    #[test]
+    fn shipped_fonts_parse() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let text = ::std::fs::read_to_string(root.join("resources/lazyfont.fnt")).unwrap();
+        let (glyphs, page) = Glyphs::parse_fnt(&text).unwrap();
+        assert_eq!(page, "lazyfont.png");
+        assert!(glyphs.check_fits(256, 256).is_ok());
+    }
+}
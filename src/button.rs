@@ -0,0 +1,210 @@
+//! A clickable button, driven by mouse events.
+//!
+//! Lesson 17's `LButton` looked at the mouse state once a frame and
+//! compared it with a global button size, and never forgot that it had
+//! been pressed.  `Button` has its own rectangle, and works out its
+//! state from the `MouseMotion`, `MouseButtonDown` and `MouseButtonUp`
+//! events passed to `handle_event`:
+//!
+//! * `Out` - the mouse isn't over the button
+//! * `Over` - it is, and the left button isn't held
+//! * `Down` - the left button was pressed on the button, and is still
+//!   held with the mouse over it
+//! * `Up` - the left button has just been released over the button it
+//!   was pressed on, which is a click.  The next movement puts the
+//!   button back to `Over`.
+//!
+//! Pressing somewhere else and releasing over the button isn't a click,
+//! and neither is pressing on the button and releasing somewhere else;
+//! a press can be dragged off the button and back on again, though.
+//! Nothing here needs SDL to be running, so the states can be tested
+//! with made-up events.
+
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+/// What the mouse is doing to the button.  The variants are in the
+/// order their images usually appear on a button's sprite sheet, so
+/// `state as usize` can index a list of clips.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonState {
+    Out = 0,
+    Over,
+    Down,
+    Up,
+}
+
+/// A rectangle on screen that can be clicked.
+pub struct Button<'a> {
+    rect: Rect,
+    state: ButtonState,
+    // The left button went down on us and hasn't been released yet
+    pressed: bool,
+    on_click: Vec<Box<dyn FnMut() + 'a>>,
+}
+
+impl<'a> Button<'a> {
+
+    /// A button covering `rect`.
+    pub fn new(rect: Rect) -> Button<'a> {
+        Button {
+            rect,
+            state: ButtonState::Out,
+            pressed: false,
+            on_click: Vec::new(),
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Move the button, keeping its size.
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.rect.set_x(x);
+        self.rect.set_y(y);
+    }
+
+    pub fn state(&self) -> ButtonState {
+        self.state
+    }
+
+    /// Call `callback` every time the button is clicked.
+    pub fn on_click<F: FnMut() + 'a>(&mut self, callback: F) {
+        self.on_click.push(Box::new(callback));
+    }
+
+    // Unlike Rect::contains, the right and bottom edges are outside
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.rect.x() && x < self.rect.x() + self.rect.width() as i32 &&
+            y >= self.rect.y() && y < self.rect.y() + self.rect.height() as i32
+    }
+
+    /// Update the button's state from a mouse event; anything else is
+    /// ignored.  Returns true if the event clicked the button, after
+    /// running the click callbacks.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::MouseMotion { x, y, .. } => {
+                self.state = match (self.contains(x, y), self.pressed) {
+                    (false, _) => ButtonState::Out,
+                    (true, true) => ButtonState::Down,
+                    (true, false) => ButtonState::Over,
+                };
+            },
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. }
+                if self.contains(x, y) => {
+                self.pressed = true;
+                self.state = ButtonState::Down;
+            },
+            Event::MouseButtonUp { mouse_btn: MouseButton::Left, x, y, .. } => {
+                let was_pressed = self.pressed;
+                self.pressed = false;
+                if !self.contains(x, y) {
+                    self.state = ButtonState::Out;
+                } else if was_pressed {
+                    self.state = ButtonState::Up;
+                    for callback in &mut self.on_click {
+                        callback();
+                    }
+                    return true;
+                } else {
+                    self.state = ButtonState::Over;
+                }
+            },
+            _ => {},
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use sdl2::mouse::MouseState;
+
+    fn motion(x: i32, y: i32) -> Event {
+        Event::MouseMotion { timestamp: 0, window_id: 0, which: 0,
+                             mousestate: MouseState::from_sdl_state(0),
+                             x, y, xrel: 0, yrel: 0 }
+    }
+
+    fn press(mouse_btn: MouseButton, x: i32, y: i32) -> Event {
+        Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn, x, y }
+    }
+
+    fn release(mouse_btn: MouseButton, x: i32, y: i32) -> Event {
+        Event::MouseButtonUp { timestamp: 0, window_id: 0, which: 0, mouse_btn, x, y }
+    }
+
+    fn button<'a>() -> Button<'a> {
+        Button::new(Rect::new(100, 100, 50, 20))
+    }
+
+    #[test]
+    fn hover_press_release_is_a_click() {
+        let clicks = Cell::new(0);
+        let mut button = button();
+        button.on_click(|| clicks.set(clicks.get() + 1));
+        assert_eq!(button.state(), ButtonState::Out);
+
+        button.handle_event(&motion(110, 110));
+        assert_eq!(button.state(), ButtonState::Over);
+        button.handle_event(&press(MouseButton::Left, 110, 110));
+        assert_eq!(button.state(), ButtonState::Down);
+        assert!(button.handle_event(&release(MouseButton::Left, 110, 110)));
+        assert_eq!(button.state(), ButtonState::Up);
+        assert_eq!(clicks.get(), 1);
+
+        // Up only lasts until the mouse moves again
+        button.handle_event(&motion(111, 110));
+        assert_eq!(button.state(), ButtonState::Over);
+        button.handle_event(&motion(10, 10));
+        assert_eq!(button.state(), ButtonState::Out);
+    }
+
+    #[test]
+    fn presses_can_be_dragged_off_and_back() {
+        let mut button = button();
+        button.handle_event(&press(MouseButton::Left, 110, 110));
+        button.handle_event(&motion(200, 200));
+        assert_eq!(button.state(), ButtonState::Out);
+        button.handle_event(&motion(120, 110));
+        assert_eq!(button.state(), ButtonState::Down);
+        assert!(button.handle_event(&release(MouseButton::Left, 120, 110)));
+    }
+
+    #[test]
+    fn releasing_elsewhere_is_not_a_click() {
+        let mut button = button();
+        button.handle_event(&press(MouseButton::Left, 110, 110));
+        assert!(!button.handle_event(&release(MouseButton::Left, 10, 10)));
+        assert_eq!(button.state(), ButtonState::Out);
+        // ... and nor is pressing elsewhere and releasing on the button
+        button.handle_event(&press(MouseButton::Left, 10, 10));
+        assert!(!button.handle_event(&release(MouseButton::Left, 110, 110)));
+        assert_eq!(button.state(), ButtonState::Over);
+    }
+
+    #[test]
+    fn only_the_left_button_clicks() {
+        let mut button = button();
+        button.handle_event(&press(MouseButton::Right, 110, 110));
+        assert_eq!(button.state(), ButtonState::Out);
+        assert!(!button.handle_event(&release(MouseButton::Right, 110, 110)));
+    }
+
+    #[test]
+    fn the_far_edges_are_outside() {
+        let mut button = button();
+        button.handle_event(&motion(149, 119));
+        assert_eq!(button.state(), ButtonState::Over);
+        button.handle_event(&motion(150, 110));
+        assert_eq!(button.state(), ButtonState::Out);
+        button.set_position(150, 100);
+        button.handle_event(&motion(150, 110));
+        assert_eq!(button.state(), ButtonState::Over);
+    }
+}
@@ -0,0 +1,173 @@
+//! Frame counting and capture for scripted runs.
+//!
+//! `--frames N` makes a lesson quit after drawing N frames, and
+//! `--capture path.png` saves the last of them (see `options`).  The
+//! golden-image tests in `tests/golden.rs` use both to run each lesson
+//! headless and compare what it drew against a reference image.
+//!
+//! The lessons create a `FrameCapture` before their game loop and call
+//! `frame` once per pass, just before `present`.  Without `--frames`
+//! this does nothing, and the lesson runs until it is closed.
+
+use std::path::{Path, PathBuf};
+
+use sdl2::render::Renderer;
+use sdl2::surface::Surface;
+
+use sdl2::image::{LoadSurface, SaveSurface};
+
+use error::{Error, Result};
+use texture::{PixelBuffer, PIXEL_FORMAT};
+
+/// Counts the frames a lesson renders, and saves the last one.
+pub struct FrameCapture {
+    frames: Option<u32>,
+    output: Option<PathBuf>,
+    rendered: u32,
+}
+
+impl FrameCapture {
+
+    /// Run for `frames` frames (or forever if `None`), saving the last
+    /// one to `output` if it is set.
+    pub fn new(frames: Option<u32>, output: Option<PathBuf>) -> FrameCapture {
+        FrameCapture {
+            frames,
+            output,
+            rendered: 0,
+        }
+    }
+
+    /// True for scripted runs, where the lesson should skip anything
+    /// that only makes sense with a person watching, like pausing
+    /// before it exits.
+    pub fn is_headless(&self) -> bool {
+        self.frames.is_some()
+    }
+
+    /// Call once per pass through the game loop, after drawing and
+    /// before `present`.  Returns false once the requested number of
+    /// frames has been drawn, after saving the last one.
+    pub fn frame(&mut self, renderer: &Renderer) -> Result<bool> {
+        if self.advance() {
+            return Ok(true);
+        }
+        if let Some(ref path) = self.output {
+            save_frame(renderer, path)?;
+        }
+        Ok(false)
+    }
+
+    // Count a frame, returning false if it was the last one
+    fn advance(&mut self) -> bool {
+        self.rendered += 1;
+        match self.frames {
+            Some(frames) => self.rendered < frames,
+            None         => true,
+        }
+    }
+}
+
+/// Read back the current render target and save it as a PNG.  It is
+/// read as R, G, B, A bytes, so the pixel data is the same whatever
+/// the window's format is.
+pub fn save_frame(renderer: &Renderer, path: &Path) -> Result<()> {
+    let (width, height) = renderer.output_size()
+        .map_err(Error::texture)?;
+    let mut pixels = renderer.read_pixels(None, PIXEL_FORMAT)
+        .map_err(Error::texture)?;
+    let surface = Surface::from_data(&mut pixels, width, height, width * 4, PIXEL_FORMAT)
+        .map_err(Error::texture)?;
+    surface.save(path).map_err(|err| Error::image(path, err))
+}
+
+/// Load an image from disk as tightly packed RGBA bytes, returning
+/// its width, height and pixels.
+pub fn load_rgba(path: &Path) -> Result<(u32, u32, Vec<u8>)> {
+    Error::check_asset(path)?;
+    let mut image = Surface::from_file(path)
+        .map_err(|err| Error::image(path, err))?;
+
+    // Convert to a known format, rather than trusting whatever layout
+    // SDL_image picked for the file.
+    let image = PixelBuffer::from_surface(&mut image)
+        .map_err(|err| Error::image(path, err))?;
+    Ok((image.width, image.height, image.pixels.into_flattened()))
+}
+
+/// How far apart two images are.
+#[derive(Debug, PartialEq)]
+pub struct Comparison {
+    /// Pixels where any channel differs by more than the tolerance
+    pub mismatched: usize,
+    /// The largest difference seen in any channel of any pixel
+    pub max_delta: u8,
+}
+
+/// Compare two RGBA buffers of the same dimensions.  A pixel only
+/// counts as mismatched if one of its channels is off by more than
+/// `tolerance`, which leaves room for rounding differences between
+/// SDL versions in blending and scaling.
+pub fn compare_rgba(actual: &[u8], expected: &[u8], tolerance: u8) -> Comparison {
+    assert_eq!(actual.len(), expected.len(), "images differ in size");
+    let mut comparison = Comparison { mismatched: 0, max_delta: 0 };
+    for (a, e) in actual.chunks(4).zip(expected.chunks(4)) {
+        let delta = a.iter().zip(e)
+            .map(|(&a, &e)| a.abs_diff(e))
+            .max()
+            .unwrap_or(0);
+        if delta > tolerance {
+            comparison.mismatched += 1;
+        }
+        if delta > comparison.max_delta {
+            comparison.max_delta = delta;
+        }
+    }
+    comparison
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_images_match() {
+        let pixels = [10, 20, 30, 255, 40, 50, 60, 255];
+        assert_eq!(compare_rgba(&pixels, &pixels, 0),
+                   Comparison { mismatched: 0, max_delta: 0 });
+    }
+
+    #[test]
+    fn differences_within_tolerance_are_ignored() {
+        let actual = [10, 20, 30, 255, 40, 50, 60, 255];
+        let expected = [12, 20, 30, 255, 40, 49, 60, 255];
+        assert_eq!(compare_rgba(&actual, &expected, 2),
+                   Comparison { mismatched: 0, max_delta: 2 });
+    }
+
+    #[test]
+    fn differences_beyond_tolerance_are_counted_per_pixel() {
+        let actual = [10, 20, 30, 255, 40, 50, 60, 255];
+        let expected = [0, 0, 30, 255, 40, 50, 60, 128];
+        assert_eq!(compare_rgba(&actual, &expected, 2),
+                   Comparison { mismatched: 2, max_delta: 127 });
+    }
+
+    #[test]
+    fn frame_limit_stops_the_loop() {
+        let mut capture = FrameCapture::new(Some(3), None);
+        assert!(capture.is_headless());
+        assert!(capture.advance());
+        assert!(capture.advance());
+        assert!(!capture.advance());
+    }
+
+    #[test]
+    fn no_frame_limit_runs_forever() {
+        let mut capture = FrameCapture::new(None, None);
+        assert!(!capture.is_headless());
+        for _ in 0..1000 {
+            assert!(capture.advance());
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! The screens attached to the machine, and the modes they can run in.
+//!
+//! SDL numbers the displays from 0.  Each has a name, bounds - where it
+//! sits on the desktop, which is one big coordinate space with the
+//! primary display's top left corner at (0, 0) - and a list of display
+//! modes (size, refresh rate and pixel format) it supports, largest
+//! first.  `Display::query` gathers all of that up for one display, and
+//! `displays` for every one, so a lesson can show it or pick from it
+//! without calling into the video subsystem a mode at a time.
+//!
+//! A window is on whichever display holds most of it, so moving it to
+//! another display is a matter of moving it inside that display's
+//! bounds; `move_to_display` centres it there.
+
+use std::fmt;
+
+use sdl2::VideoSubsystem;
+use sdl2::rect::Rect;
+use sdl2::video::{DisplayMode, WindowPos, WindowRef};
+
+use error::{Error, Result};
+
+/// What a mode is, in a form that can be shown: `1920x1080, 60 Hz,
+/// 24 bpp`.
+pub struct ModeName<'a>(pub &'a DisplayMode);
+
+impl<'a> fmt::Display for ModeName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mode = self.0;
+        write!(f, "{}x{}", mode.w, mode.h)?;
+        // Some drivers don't know the refresh rate, and say 0
+        if mode.refresh_rate > 0 {
+            write!(f, ", {} Hz", mode.refresh_rate)?;
+        }
+        // SDL_BITSPERPIXEL: the second byte of the pixel format
+        let bits = (mode.format >> 8) & 0xff;
+        if bits > 0 {
+            write!(f, ", {} bpp", bits)?;
+        }
+        Ok(())
+    }
+}
+
+/// One display, as SDL describes it.
+#[derive(Debug)]
+pub struct Display {
+    pub index: i32,
+    pub name: String,
+    /// Where the display is on the desktop
+    pub bounds: Rect,
+    /// The mode it is running in now
+    pub current: DisplayMode,
+    /// The mode it was in when SDL started, which fullscreen desktop
+    /// windows use
+    pub desktop: DisplayMode,
+    /// Every mode it can run in, largest first
+    pub modes: Vec<DisplayMode>,
+}
+
+impl Display {
+
+    /// Ask SDL about display `index`.
+    pub fn query(video: &VideoSubsystem, index: i32) -> Result<Display> {
+        let count = video.num_display_modes(index).map_err(Error::video)?;
+        let modes = (0..count)
+            .map(|mode| video.display_mode(index, mode))
+            .collect::<::std::result::Result<Vec<_>, _>>()
+            .map_err(Error::video)?;
+        Ok(Display {
+            index,
+            name: video.display_name(index),
+            bounds: video.display_bounds(index).map_err(Error::video)?,
+            current: video.current_display_mode(index).map_err(Error::video)?,
+            desktop: video.desktop_display_mode(index).map_err(Error::video)?,
+            modes,
+        })
+    }
+}
+
+/// Every display, in SDL's order.
+pub fn displays(video: &VideoSubsystem) -> Result<Vec<Display>> {
+    let count = video.num_video_displays().map_err(Error::video)?;
+    (0..count).map(|index| Display::query(video, index)).collect()
+}
+
+/// Where to put the top left corner of a `width` x `height` window to
+/// centre it in `bounds`.  A window bigger than the display is lined
+/// up with its top left corner instead, so its title bar stays on it.
+pub fn centered_in(bounds: Rect, width: u32, height: u32) -> (i32, i32) {
+    let x = bounds.x() + (bounds.width() as i32 - width as i32).max(0) / 2;
+    let y = bounds.y() + (bounds.height() as i32 - height as i32).max(0) / 2;
+    (x, y)
+}
+
+/// Move `window` to the middle of `display`.
+pub fn move_to_display(window: &mut WindowRef, display: &Display) {
+    let (width, height) = window.size();
+    let (x, y) = centered_in(display.bounds, width, height);
+    window.set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_describe_themselves() {
+        // SDL_PIXELFORMAT_RGB888
+        let mode = DisplayMode::new(0x16161804, 1920, 1080, 60);
+        assert_eq!(ModeName(&mode).to_string(), "1920x1080, 60 Hz, 24 bpp");
+        let unknown = DisplayMode::new(0, 1024, 768, 0);
+        assert_eq!(ModeName(&unknown).to_string(), "1024x768");
+    }
+
+    #[test]
+    fn windows_are_centred_on_a_display() {
+        // A second display to the right of a 1920x1080 one
+        let bounds = Rect::new(1920, 0, 1280, 1024);
+        assert_eq!(centered_in(bounds, 640, 480), (2240, 272));
+        // Too big: keep the corner on the display
+        assert_eq!(centered_in(bounds, 2000, 480), (1920, 272));
+    }
+}
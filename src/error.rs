@@ -0,0 +1,141 @@
+use std::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::result;
+
+use resources::OVERRIDE_VAR;
+
+/// Everything that can go wrong while a lesson is setting itself up.
+///
+/// rust-sdl2 reports most failures as a plain `String` (or as one of
+/// a handful of small error types that wrap one), which tells you what
+/// SDL said but not what we were trying to do at the time.  Each
+/// variant here records that context, so a missing PNG is reported as
+/// "could not load image resources/foo.png" rather than a bare SDL
+/// message.
+#[derive(Debug)]
+pub enum Error {
+    /// SDL, or one of its subsystems, failed to initialize.  This
+    /// covers the core library, the video subsystem, windows,
+    /// renderers, the event pump and the image/ttf extensions.
+    Init { subsystem: &'static str, message: String },
+    /// SDL_image could not load an image
+    Image { path: PathBuf, message: String },
+    /// SDL_ttf could not load a font (`path` is set) or render text
+    Ttf { path: Option<PathBuf>, message: String },
+    /// A surface could not be turned into a texture
+    Texture(String),
+    /// A texture could not be drawn
+    Render(String),
+    /// An event could not be pushed onto SDL's event queue
+    Event(String),
+    /// A window or display could not be queried or changed, e.g. when
+    /// switching to fullscreen
+    Video(String),
+    /// A data file (sprite sheet, key bindings, map...) could not be
+    /// read, or doesn't make sense
+    Data { path: PathBuf, message: String },
+    /// An asset that a lesson needs is not on disk
+    MissingAsset(PathBuf),
+    /// No asset has been added under this name, or it hasn't been loaded
+    UnknownAsset(String),
+    /// The command line could not be parsed
+    Usage(String),
+}
+
+/// Shorthand used by every fallible function in the lessons.
+pub type Result<T> = result::Result<T, Error>;
+
+impl Error {
+    /// Wrap an initialization failure of the named subsystem.
+    pub fn init<E: fmt::Display>(subsystem: &'static str, err: E) -> Error {
+        Error::Init { subsystem, message: err.to_string() }
+    }
+
+    /// Wrap a failure to load the image at `path`.
+    pub fn image<E: fmt::Display>(path: &Path, err: E) -> Error {
+        Error::Image { path: path.to_path_buf(), message: err.to_string() }
+    }
+
+    /// Wrap a failure to load the font at `path`.
+    pub fn font<E: fmt::Display>(path: &Path, err: E) -> Error {
+        Error::Ttf { path: Some(path.to_path_buf()), message: err.to_string() }
+    }
+
+    /// Wrap a failure to render text with an already loaded font.
+    pub fn text<E: fmt::Display>(err: E) -> Error {
+        Error::Ttf { path: None, message: err.to_string() }
+    }
+
+    /// Wrap a failure to create a texture.
+    pub fn texture<E: fmt::Display>(err: E) -> Error {
+        Error::Texture(err.to_string())
+    }
+
+    /// Wrap a failure to draw a texture.
+    pub fn render<E: fmt::Display>(err: E) -> Error {
+        Error::Render(err.to_string())
+    }
+
+    /// Wrap a failure to push an event onto the queue.
+    pub fn event<E: fmt::Display>(err: E) -> Error {
+        Error::Event(err.to_string())
+    }
+
+    /// Wrap a failure to query or change a window or display.
+    pub fn video<E: fmt::Display>(err: E) -> Error {
+        Error::Video(err.to_string())
+    }
+
+    /// Wrap a problem with the data file at `path`.
+    pub fn data<E: fmt::Display>(path: &Path, err: E) -> Error {
+        Error::Data { path: path.to_path_buf(), message: err.to_string() }
+    }
+
+    /// Return `Error::MissingAsset` if there is nothing at `path`.
+    ///
+    /// SDL would fail to open the file anyway, but its message does
+    /// not always make it obvious that the file simply isn't there.
+    pub fn check_asset(path: &Path) -> Result<()> {
+        if path.exists() {
+            Ok(())
+        } else {
+            Err(Error::MissingAsset(path.to_path_buf()))
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Init { subsystem, ref message } =>
+                write!(f, "could not initialize {}: {}", subsystem, message),
+            Error::Image { ref path, ref message } =>
+                write!(f, "could not load image {}: {}", path.display(), message),
+            Error::Ttf { path: Some(ref path), ref message } =>
+                write!(f, "could not load font {}: {}", path.display(), message),
+            Error::Ttf { path: None, ref message } =>
+                write!(f, "could not render text: {}", message),
+            Error::Texture(ref message) =>
+                write!(f, "could not create texture: {}", message),
+            Error::Render(ref message) =>
+                write!(f, "could not render texture: {}", message),
+            Error::Event(ref message) =>
+                write!(f, "could not push event: {}", message),
+            Error::Video(ref message) =>
+                write!(f, "window or display error: {}", message),
+            Error::Data { ref path, ref message } =>
+                write!(f, "could not load {}: {}", path.display(), message),
+            Error::MissingAsset(ref path) =>
+                write!(f, "missing asset {} (run from the repository root, set {} to the \
+                           resources directory, or build with --features embed-resources)",
+                       path.display(), OVERRIDE_VAR),
+            Error::UnknownAsset(ref name) =>
+                write!(f, "no asset named '{}' has been loaded", name),
+            Error::Usage(ref message) =>
+                write!(f, "{} (run with --help for a list of options)", message),
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,588 @@
+//! Game controllers and joysticks, plugged in and out while a lesson
+//! runs.
+//!
+//! SDL has two ways of reading a gamepad.  The joystick API numbers
+//! its axes and buttons however the hardware does; the game controller
+//! API uses a *mapping* to present anything it recognises as an Xbox
+//! style pad, with named sticks, triggers and buttons.  Mappings come
+//! in the community `gamecontrollerdb.txt` format, one pad per line:
+//!
+//! ```text
+//! # GUID, name, then which SDL input each button and axis is
+//! 030000005e0400008e02000014010000,X360 Controller,a:b0,b:b1,...,leftx:a0,lefty:a1,platform:Linux,
+//! ```
+//!
+//! `Gamepads` loads such a file, opens each device as SDL reports it
+//! being plugged in - as a game controller if there is a mapping for
+//! it, or as a plain joystick otherwise - and keeps track of its axes
+//! and buttons from the events that follow.
+//!
+//! It only ever learns about devices from events, so it can't tell a
+//! real pad from events pushed onto the queue with
+//! `EventSubsystem::push_event` (or `EventSource::push`).  A controller
+//! that won't open as one is read as a plain joystick instead.  A device
+//! it can't open at all, or any pad it hears from without being told it
+//! was plugged in, becomes a virtual pad that only exists in the
+//! events.  That is how lesson 19 runs with no gamepad attached, and
+//! how the tests below drive it.
+//!
+//! Sticks rarely rest at exactly 0, so readings within a dead zone of
+//! the centre count as 0.  `DEAD_ZONE` is the usual 8000 out of 32767.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use sdl2::{GameControllerSubsystem, JoystickSubsystem, Sdl};
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+use sdl2::joystick::Joystick;
+
+use error::{Error, Result};
+use resources;
+
+/// How far a stick can move from the centre and still read as 0.
+pub const DEAD_ZONE: i16 = 8000;
+
+// SDL's axes and buttons, in the order of their numbers
+const AXES: [Axis; 6] = [
+    Axis::LeftX, Axis::LeftY, Axis::RightX, Axis::RightY,
+    Axis::TriggerLeft, Axis::TriggerRight,
+];
+const BUTTONS: [Button; 15] = [
+    Button::A, Button::B, Button::X, Button::Y, Button::Back, Button::Guide,
+    Button::Start, Button::LeftStick, Button::RightStick, Button::LeftShoulder,
+    Button::RightShoulder, Button::DPadUp, Button::DPadDown, Button::DPadLeft,
+    Button::DPadRight,
+];
+
+/// An axis reading from -1.0 to 1.0, with anything within `dead_zone`
+/// of the centre read as 0.  The rest of the range is stretched to
+/// fill the gap, so the value still starts from 0 at the dead zone's
+/// edge instead of jumping.
+pub fn axis_value(raw: i16, dead_zone: i16) -> f64 {
+    let dead_zone = f64::from(dead_zone.max(0));
+    let raw = (f64::from(raw) / 32767.0).max(-1.0);
+    let edge = dead_zone / 32767.0;
+    if raw.abs() <= edge || edge >= 1.0 {
+        0.0
+    } else {
+        raw.signum() * (raw.abs() - edge) / (1.0 - edge)
+    }
+}
+
+/// Where a stick is pointing, as `(x, y)` from -1.0 to 1.0.  Unlike
+/// reading each axis with `axis_value`, the dead zone is a circle, so
+/// pushing the stick diagonally doesn't snap to the nearest axis.
+pub fn stick_value(x: i16, y: i16, dead_zone: i16) -> (f64, f64) {
+    let (x, y) = ((f64::from(x) / 32767.0).max(-1.0), (f64::from(y) / 32767.0).max(-1.0));
+    let distance = x.hypot(y);
+    let edge = f64::from(dead_zone.max(0)) / 32767.0;
+    if distance <= edge || edge >= 1.0 {
+        return (0.0, 0.0);
+    }
+    let scale = ((distance.min(1.0) - edge) / (1.0 - edge)) / distance;
+    (x * scale, y * scale)
+}
+
+/// One line of a `gamecontrollerdb.txt` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mapping {
+    /// The 32 hex digit joystick GUID the mapping is for
+    pub guid: String,
+    pub name: String,
+    /// The `platform:` field, for mappings that only apply to one OS
+    pub platform: Option<String>,
+    line: String,
+}
+
+impl Mapping {
+
+    /// Parse one mapping line.
+    pub fn parse(line: &str) -> ::std::result::Result<Mapping, String> {
+        let line = line.trim();
+        let mut fields = line.split(',');
+        let guid = fields.next().unwrap_or("");
+        if guid.len() != 32 || !guid.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("'{}' is not a joystick GUID", guid));
+        }
+        let name = match fields.next() {
+            Some(name) if !name.trim().is_empty() => name.trim(),
+            _ => return Err(format!("mapping for {} has no name", guid)),
+        };
+        let mut platform = None;
+        for field in fields.filter(|field| !field.trim().is_empty()) {
+            match field.find(':') {
+                Some(at) if at > 0 && at + 1 < field.len() => {
+                    if &field[..at] == "platform" {
+                        platform = Some(field[at + 1..].to_string());
+                    }
+                },
+                _ => return Err(format!("'{}' in the mapping for {} should be input:binding",
+                                        field, name)),
+            }
+        }
+        Ok(Mapping {
+            guid: guid.to_string(),
+            name: name.to_string(),
+            platform,
+            line: line.to_string(),
+        })
+    }
+
+    /// The mapping as SDL expects it.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Whether the mapping applies on `platform`, named as SDL names
+    /// it ("Linux", "Windows", "Mac OS X").  Mappings without a
+    /// platform apply everywhere.
+    pub fn is_for(&self, platform: &str) -> bool {
+        self.platform.as_ref().is_none_or(|p| p == platform)
+    }
+}
+
+/// Every mapping in the text of a `gamecontrollerdb.txt` file, skipping
+/// blank lines and `#` comments.  Errors say which line is wrong.
+pub fn parse_mappings(text: &str) -> ::std::result::Result<Vec<Mapping>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|&(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(number, line)| Mapping::parse(line)
+             .map_err(|err| format!("line {}: {}", number + 1, err)))
+        .collect()
+}
+
+/// The name SDL gives the platform we were built for, as used in the
+/// `platform:` field of a mapping.
+pub fn current_platform() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "Windows"
+    } else if cfg!(target_os = "macos") {
+        "Mac OS X"
+    } else if cfg!(target_os = "android") {
+        "Android"
+    } else if cfg!(target_os = "ios") {
+        "iOS"
+    } else {
+        "Linux"
+    }
+}
+
+/// How a pad's axes and buttons are numbered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadKind {
+    /// A game controller, laid out by its mapping
+    Controller,
+    /// A joystick SDL has no mapping for.  Its axes and buttons are
+    /// read in the order the hardware numbers them, which is only a
+    /// guess at which is which.
+    Joystick,
+}
+
+// What we have open for a pad; virtual pads have nothing
+enum Device {
+    Controller(GameController),
+    Joystick(Joystick),
+    Virtual,
+}
+
+/// A gamepad that is plugged in, or pretending to be.
+pub struct Pad {
+    id: i32,
+    name: String,
+    kind: PadKind,
+    device: Device,
+    dead_zone: i16,
+    axes: [i16; 6],
+    buttons: BTreeSet<usize>,
+}
+
+impl Pad {
+
+    fn new(id: i32, name: String, kind: PadKind, device: Device, dead_zone: i16) -> Pad {
+        Pad { id, name, kind, device, dead_zone, axes: [0; 6], buttons: BTreeSet::new() }
+    }
+
+    fn new_virtual(id: i32, kind: PadKind, dead_zone: i16) -> Pad {
+        let name = match kind {
+            PadKind::Controller => format!("Virtual controller {}", id),
+            PadKind::Joystick => format!("Virtual joystick {}", id),
+        };
+        Pad::new(id, name, kind, Device::Virtual, dead_zone)
+    }
+
+    /// SDL's instance id for the pad, which its events carry as `which`.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> PadKind {
+        self.kind
+    }
+
+    /// True for a pad that only exists in the event queue.
+    pub fn is_virtual(&self) -> bool {
+        matches!(self.device, Device::Virtual)
+    }
+
+    /// False once a real pad has been unplugged, which can be a frame
+    /// or so before its `DeviceRemoved` event arrives.
+    pub fn is_attached(&self) -> bool {
+        match self.device {
+            Device::Controller(ref controller) => controller.attached(),
+            Device::Joystick(ref joystick) => joystick.attached(),
+            Device::Virtual => true,
+        }
+    }
+
+    /// The last reading of `axis`, from -32768 to 32767.
+    pub fn raw_axis(&self, axis: Axis) -> i16 {
+        self.axes[axis as usize]
+    }
+
+    /// `axis` from -1.0 to 1.0, with the dead zone applied.  Triggers
+    /// go from 0.0 to 1.0.
+    pub fn axis(&self, axis: Axis) -> f64 {
+        axis_value(self.raw_axis(axis), self.dead_zone)
+    }
+
+    /// The left stick's `(x, y)`, with a round dead zone; y is positive
+    /// downwards, as on screen.
+    pub fn left_stick(&self) -> (f64, f64) {
+        stick_value(self.raw_axis(Axis::LeftX), self.raw_axis(Axis::LeftY), self.dead_zone)
+    }
+
+    /// As `left_stick`, for the right stick.
+    pub fn right_stick(&self) -> (f64, f64) {
+        stick_value(self.raw_axis(Axis::RightX), self.raw_axis(Axis::RightY), self.dead_zone)
+    }
+
+    pub fn button(&self, button: Button) -> bool {
+        self.buttons.contains(&(button as usize))
+    }
+}
+
+/// A pad being plugged in or unplugged, by its id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hotplug {
+    Connected(i32),
+    Disconnected(i32),
+}
+
+/// Every pad that is plugged in.  See the module documentation.
+pub struct Gamepads {
+    controllers: Option<GameControllerSubsystem>,
+    joysticks: Option<JoystickSubsystem>,
+    pads: BTreeMap<i32, Pad>,
+    dead_zone: i16,
+}
+
+impl Gamepads {
+
+    /// Start SDL's game controller and joystick subsystems.  The pads
+    /// already plugged in are opened as their `DeviceAdded` events
+    /// arrive, at the first poll of the event pump.
+    pub fn new(sdl: &Sdl) -> Result<Gamepads> {
+        let controllers = sdl.game_controller()
+            .map_err(|err| Error::init("game controller subsystem", err))?;
+        let joysticks = sdl.joystick()
+            .map_err(|err| Error::init("joystick subsystem", err))?;
+        Ok(Gamepads {
+            controllers: Some(controllers),
+            joysticks: Some(joysticks),
+            ..Gamepads::detached()
+        })
+    }
+
+    /// Pads that only come from events, without SDL; every pad is
+    /// virtual.
+    pub fn detached() -> Gamepads {
+        Gamepads {
+            controllers: None,
+            joysticks: None,
+            pads: BTreeMap::new(),
+            dead_zone: DEAD_ZONE,
+        }
+    }
+
+    /// Add the mappings for this platform from a `gamecontrollerdb.txt`
+    /// file in `resources/`, returning how many there were.  Mappings
+    /// only affect pads opened afterwards, so load them before the
+    /// first poll.
+    pub fn load_mappings(&self, path: &Path) -> Result<usize> {
+        let text = resources::read_to_string(path)?;
+        let mappings = parse_mappings(&text).map_err(|err| Error::data(path, err))?;
+        let mut count = 0;
+        for mapping in mappings.iter().filter(|mapping| mapping.is_for(current_platform())) {
+            if let Some(ref controllers) = self.controllers {
+                controllers.add_mapping(mapping.line())
+                    .map_err(|err| Error::data(path, format!("{}: {}", mapping.name, err)))?;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Change the dead zone of every pad, now and to come.
+    pub fn set_dead_zone(&mut self, dead_zone: i16) {
+        self.dead_zone = dead_zone.max(0);
+        for pad in self.pads.values_mut() {
+            pad.dead_zone = self.dead_zone;
+        }
+    }
+
+    pub fn dead_zone(&self) -> i16 {
+        self.dead_zone
+    }
+
+    pub fn pad(&self, id: i32) -> Option<&Pad> {
+        self.pads.get(&id)
+    }
+
+    /// The pad plugged in first (strictly, with the lowest id), for
+    /// single player lessons.
+    pub fn first(&self) -> Option<&Pad> {
+        self.pads.values().next()
+    }
+
+    pub fn pads(&self) -> impl Iterator<Item = &Pad> {
+        self.pads.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pads.is_empty()
+    }
+
+    /// Open the device at `index`.  A controller that won't open as
+    /// one is read as the joystick it also is, so that it is still
+    /// filed under the instance id its events will carry.  Only if SDL
+    /// can't open it at all - or was never asked to, for pads pushed
+    /// onto the queue - is it made up as a virtual pad, under the one
+    /// number there is.
+    fn open(&self, index: i32, kind: PadKind) -> Pad {
+        let dead_zone = self.dead_zone;
+        let opened = match (kind, self.controllers.as_ref()) {
+            _ if index < 0 => None,
+            (PadKind::Controller, Some(controllers)) => {
+                controllers.open(index as u32).ok()
+                    .map(|controller| {
+                        Pad::new(controller.instance_id(), controller.name(), kind,
+                                 Device::Controller(controller), dead_zone)
+                    })
+                    .or_else(|| self.open_joystick(index as u32))
+            },
+            _ => self.open_joystick(index as u32),
+        };
+        opened.unwrap_or_else(|| Pad::new_virtual(index, kind, dead_zone))
+    }
+
+    // Open the device at `index` as a plain joystick
+    fn open_joystick(&self, index: u32) -> Option<Pad> {
+        let joystick = self.joysticks.as_ref()?.open(index).ok()?;
+        Some(Pad::new(joystick.instance_id(), joystick.name(), PadKind::Joystick,
+                      Device::Joystick(joystick), self.dead_zone))
+    }
+
+    // The pad `id`, of `kind`, adding a virtual one if we've never
+    // heard of it.  None if it is there, but of the other kind.
+    fn pad_of_kind(&mut self, id: i32, kind: PadKind, change: &mut Option<Hotplug>)
+                   -> Option<&mut Pad> {
+        let dead_zone = self.dead_zone;
+        let pad = self.pads.entry(id).or_insert_with(|| {
+            *change = Some(Hotplug::Connected(id));
+            Pad::new_virtual(id, kind, dead_zone)
+        });
+        if pad.kind == kind { Some(pad) } else { None }
+    }
+
+    /// Keep track of pads from `event`; anything other than a
+    /// controller or joystick event is ignored.  Returns what was
+    /// plugged in or unplugged, if anything was.
+    ///
+    /// SDL sends the joystick events for a game controller as well as
+    /// the controller ones.  Those are ignored, so that each pad is
+    /// only read one way.
+    pub fn handle_event(&mut self, event: &Event) -> Option<Hotplug> {
+        let mut change = None;
+        match *event {
+            // `which` is the device index for these two...
+            Event::ControllerDeviceAdded { which, .. } => {
+                let pad = self.open(which, PadKind::Controller);
+                change = Some(Hotplug::Connected(pad.id));
+                self.pads.insert(pad.id, pad);
+            },
+            Event::JoyDeviceAdded { which, .. } => {
+                // A controller gets its own ControllerDeviceAdded
+                let is_controller = which >= 0 && self.controllers.as_ref()
+                    .is_some_and(|controllers| controllers.is_game_controller(which as u32));
+                if !is_controller {
+                    let pad = self.open(which, PadKind::Joystick);
+                    change = Some(Hotplug::Connected(pad.id));
+                    self.pads.insert(pad.id, pad);
+                }
+            },
+            // ... and the instance id for everything else
+            Event::ControllerDeviceRemoved { which, .. } |
+            Event::JoyDeviceRemoved { which, .. } => {
+                change = self.pads.remove(&which).map(|_| Hotplug::Disconnected(which));
+            },
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                if let Some(pad) = self.pad_of_kind(which, PadKind::Controller, &mut change) {
+                    pad.axes[axis as usize] = value;
+                }
+            },
+            Event::ControllerButtonDown { which, button, .. } => {
+                if let Some(pad) = self.pad_of_kind(which, PadKind::Controller, &mut change) {
+                    pad.buttons.insert(button as usize);
+                }
+            },
+            Event::ControllerButtonUp { which, button, .. } => {
+                if let Some(pad) = self.pad_of_kind(which, PadKind::Controller, &mut change) {
+                    pad.buttons.remove(&(button as usize));
+                }
+            },
+            Event::JoyAxisMotion { which, axis_idx, value, .. } => {
+                if let Some(pad) = self.pad_of_kind(which, PadKind::Joystick, &mut change) {
+                    if let Some(axis) = AXES.get(axis_idx as usize) {
+                        pad.axes[*axis as usize] = value;
+                    }
+                }
+            },
+            Event::JoyButtonDown { which, button_idx, .. } => {
+                if let Some(pad) = self.pad_of_kind(which, PadKind::Joystick, &mut change) {
+                    if (button_idx as usize) < BUTTONS.len() {
+                        pad.buttons.insert(button_idx as usize);
+                    }
+                }
+            },
+            Event::JoyButtonUp { which, button_idx, .. } => {
+                if let Some(pad) = self.pad_of_kind(which, PadKind::Joystick, &mut change) {
+                    pad.buttons.remove(&(button_idx as usize));
+                }
+            },
+            _ => {},
+        }
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis_motion(which: i32, axis: Axis, value: i16) -> Event {
+        Event::ControllerAxisMotion { timestamp: 0, which, axis, value }
+    }
+
+    #[test]
+    fn dead_zones_read_as_zero() {
+        assert_eq!(axis_value(DEAD_ZONE, DEAD_ZONE), 0.0);
+        assert_eq!(axis_value(-DEAD_ZONE, DEAD_ZONE), 0.0);
+        assert_eq!(axis_value(32767, DEAD_ZONE), 1.0);
+        assert_eq!(axis_value(-32768, DEAD_ZONE), -1.0);
+        // Just outside the dead zone is only just above 0
+        assert!(axis_value(DEAD_ZONE + 10, DEAD_ZONE) < 0.01);
+        assert_eq!(axis_value(16384, 0), 16384.0 / 32767.0);
+
+        assert_eq!(stick_value(6000, 6000, DEAD_ZONE), (0.0, 0.0));
+        let (x, y) = stick_value(20000, 20000, DEAD_ZONE);
+        assert!(x > 0.0 && x == y);
+        let (x, y) = stick_value(32767, -32768, DEAD_ZONE);
+        assert!((x.hypot(y) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn mappings_parse() {
+        let text = "\
+# A comment, then a blank line
+
+030000005e0400008e02000014010000,X360 Controller,a:b0,b:b1,leftx:a0,lefty:a1,platform:Linux,
+03000000790000000600000000000000,G-Shark GS-GP702,a:b2,b:b1,leftx:a0,lefty:a1,
+";
+        let mappings = parse_mappings(text).unwrap();
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0].name, "X360 Controller");
+        assert_eq!(mappings[0].platform, Some("Linux".to_string()));
+        assert!(mappings[0].is_for("Linux") && !mappings[0].is_for("Windows"));
+        assert!(mappings[1].is_for("Windows"));
+        assert!(mappings[1].line().starts_with("0300000079"));
+
+        let err = parse_mappings("\n\nnot-a-guid,Pad,a:b0,").unwrap_err();
+        assert!(err.starts_with("line 3:"), "{}", err);
+        assert!(parse_mappings("030000005e0400008e02000014010000,Pad,a").is_err());
+        assert!(parse_mappings("030000005e0400008e02000014010000,").is_err());
+    }
+
+    #[test]
+    fn unknown_pads_are_virtual() {
+        let mut pads = Gamepads::detached();
+        assert_eq!(pads.handle_event(&axis_motion(3, Axis::LeftX, 32767)),
+                   Some(Hotplug::Connected(3)));
+        assert_eq!(pads.handle_event(&axis_motion(3, Axis::LeftY, -32768)), None);
+        let pad = pads.first().unwrap();
+        assert!(pad.is_virtual());
+        assert_eq!(pad.kind(), PadKind::Controller);
+        assert_eq!(pad.axis(Axis::LeftX), 1.0);
+        assert_eq!(pad.axis(Axis::LeftY), -1.0);
+
+        pads.handle_event(&Event::ControllerButtonDown { timestamp: 0, which: 3,
+                                                         button: Button::Start });
+        assert!(pads.pad(3).unwrap().button(Button::Start));
+        pads.handle_event(&Event::ControllerButtonUp { timestamp: 0, which: 3,
+                                                       button: Button::Start });
+        assert!(!pads.pad(3).unwrap().button(Button::Start));
+    }
+
+    #[test]
+    fn pads_come_and_go() {
+        let mut pads = Gamepads::detached();
+        assert_eq!(pads.handle_event(&Event::ControllerDeviceAdded { timestamp: 0, which: 0 }),
+                   Some(Hotplug::Connected(0)));
+        assert_eq!(pads.handle_event(&Event::JoyDeviceAdded { timestamp: 0, which: 1 }),
+                   Some(Hotplug::Connected(1)));
+        assert_eq!(pads.len(), 2);
+        assert_eq!(pads.pad(1).unwrap().kind(), PadKind::Joystick);
+
+        // A joystick's axes are read in controller order
+        pads.handle_event(&Event::JoyAxisMotion { timestamp: 0, which: 1, axis_idx: 2,
+                                                  value: 20000 });
+        assert_eq!(pads.pad(1).unwrap().raw_axis(Axis::RightX), 20000);
+        // ... but a controller's joystick events are ignored
+        pads.handle_event(&Event::JoyAxisMotion { timestamp: 0, which: 0, axis_idx: 0,
+                                                  value: 20000 });
+        assert_eq!(pads.pad(0).unwrap().raw_axis(Axis::LeftX), 0);
+
+        assert_eq!(pads.handle_event(&Event::ControllerDeviceRemoved { timestamp: 0, which: 0 }),
+                   Some(Hotplug::Disconnected(0)));
+        // SDL sends JoyDeviceRemoved for it too
+        assert_eq!(pads.handle_event(&Event::JoyDeviceRemoved { timestamp: 0, which: 0 }), None);
+        assert_eq!(pads.first().unwrap().id(), 1);
+    }
+
+    #[test]
+    fn the_dead_zone_can_change() {
+        let mut pads = Gamepads::detached();
+        pads.handle_event(&axis_motion(0, Axis::LeftX, 10000));
+        assert!(pads.first().unwrap().axis(Axis::LeftX) > 0.0);
+        pads.set_dead_zone(12000);
+        assert_eq!(pads.first().unwrap().axis(Axis::LeftX), 0.0);
+        assert_eq!(pads.first().unwrap().left_stick(), (0.0, 0.0));
+    }
+
+    // The mappings shipped in resources/ parse
+    #[test]
+    fn shipped_mappings_parse() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/gamecontrollerdb.txt");
+        let text = ::std::fs::read_to_string(&path).unwrap();
+        assert!(!parse_mappings(&text).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+//! Named actions, and the keys and buttons bound to them.
+//!
+//! Rather than matching on `Keycode::Q` in its event loop, a lesson asks
+//! an `InputMap` which of its actions an event triggers -
+//! `"increase_red"`, `"quit"` and so on - and matches on those.  The
+//! bindings come from a RON file that maps each action to a list of
+//! bindings:
+//!
+//! ```text
+//! {
+//!     "quit": ["Escape", "Ctrl+Q"],
+//!     // The key where Q is on a US keyboard, whatever it says on it
+//!     "increase_red": ["scancode:Q"],
+//!     "fire": ["mouse:Left", "Space"],
+//! }
+//! ```
+//!
+//! A binding is a key name as SDL spells it (`"Q"`, `"Left"`,
+//! `"Keypad +"`), `scancode:` and a scancode name for a key by its
+//! position on the keyboard rather than its label, or `mouse:` and one
+//! of `Left`, `Middle`, `Right`, `X1` or `X2`, or `pad:` and a game
+//! controller button as SDL's mappings name it (`a`, `start`,
+//! `leftshoulder`, `dpup`...).  Any of these can be
+//! prefixed with `Ctrl+`, `Shift+`, `Alt+` and `Gui+`, in which case
+//! exactly those modifiers have to be held down.
+//!
+//! Each lesson ships its defaults in `resources/lessonNN.keys.ron`.
+//! `--bindings FILE` loads a second file over the top, whose bindings
+//! replace the defaults for the actions it mentions.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use ron;
+use sdl2::controller::Button;
+use sdl2::event::Event;
+use sdl2::keyboard::{self, Keycode, Mod, Scancode};
+use sdl2::mouse::MouseButton;
+
+use error::{Error, Result};
+use resources;
+
+/// The modifier keys that have to be held for a binding to fire.
+/// Left and right are not told apart, and lock keys are ignored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub gui: bool,
+}
+
+impl Modifiers {
+
+    pub fn from_mod(keymod: Mod) -> Modifiers {
+        Modifiers {
+            ctrl: keymod.intersects(keyboard::LCTRLMOD | keyboard::RCTRLMOD),
+            shift: keymod.intersects(keyboard::LSHIFTMOD | keyboard::RSHIFTMOD),
+            alt: keymod.intersects(keyboard::LALTMOD | keyboard::RALTMOD),
+            gui: keymod.intersects(keyboard::LGUIMOD | keyboard::RGUIMOD),
+        }
+    }
+}
+
+/// What has to be pressed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Trigger {
+    /// A key, by the symbol on it in the current layout
+    Key(Keycode),
+    /// A key, by where it is on the keyboard
+    Scancode(Scancode),
+    Mouse(MouseButton),
+    /// A button on any game controller
+    Pad(Button),
+}
+
+/// A key or button, and the modifiers to hold with it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Binding {
+    pub trigger: Trigger,
+    pub modifiers: Modifiers,
+}
+
+const MOUSE_BUTTONS: [(&str, MouseButton); 5] = [
+    ("Left", MouseButton::Left),
+    ("Middle", MouseButton::Middle),
+    ("Right", MouseButton::Right),
+    ("X1", MouseButton::X1),
+    ("X2", MouseButton::X2),
+];
+
+// Strip `prefix` from the front of `text`, ignoring case
+fn strip_prefix<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    match text.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => Some(&text[prefix.len()..]),
+        _ => None,
+    }
+}
+
+impl Binding {
+
+    pub fn new(trigger: Trigger) -> Binding {
+        Binding { trigger, modifiers: Modifiers::default() }
+    }
+
+    /// Parse a binding as written in a bindings file, e.g. `"Ctrl+Q"`.
+    pub fn parse(text: &str) -> ::std::result::Result<Binding, String> {
+        let mut modifiers = Modifiers::default();
+        let mut rest = text.trim();
+        // Peel off modifiers from the front.  Splitting on '+' would
+        // break keys with a '+' in their name, like "Keypad +".
+        loop {
+            if let Some(after) = strip_prefix(rest, "ctrl+") {
+                modifiers.ctrl = true;
+                rest = after;
+            } else if let Some(after) = strip_prefix(rest, "shift+") {
+                modifiers.shift = true;
+                rest = after;
+            } else if let Some(after) = strip_prefix(rest, "alt+") {
+                modifiers.alt = true;
+                rest = after;
+            } else if let Some(after) = strip_prefix(rest, "gui+") {
+                modifiers.gui = true;
+                rest = after;
+            } else {
+                break;
+            }
+        }
+        let trigger = if let Some(name) = strip_prefix(rest, "scancode:") {
+            Scancode::from_name(name).map(Trigger::Scancode)
+        } else if let Some(name) = strip_prefix(rest, "mouse:") {
+            MOUSE_BUTTONS.iter()
+                .find(|&&(button, _)| button.eq_ignore_ascii_case(name))
+                .map(|&(_, button)| Trigger::Mouse(button))
+        } else if let Some(name) = strip_prefix(rest, "pad:") {
+            Button::from_string(name).map(Trigger::Pad)
+        } else if rest.is_empty() {
+            None
+        } else {
+            Keycode::from_name(rest).map(Trigger::Key)
+        };
+        match trigger {
+            Some(trigger) => Ok(Binding { trigger, modifiers }),
+            None => Err(format!("'{}' is not a key, scancode, mouse or pad button", text)),
+        }
+    }
+
+    /// The binding for the key or button pressed in `event`, if it is
+    /// a press; for asking the player to press the key they want.
+    pub fn from_event(event: &Event) -> Option<Binding> {
+        match *event {
+            Event::KeyDown { keycode: Some(keycode), keymod, .. } => Some(Binding {
+                trigger: Trigger::Key(keycode),
+                modifiers: Modifiers::from_mod(keymod),
+            }),
+            Event::MouseButtonDown { mouse_btn, .. } if mouse_btn != MouseButton::Unknown => {
+                Some(Binding::new(Trigger::Mouse(mouse_btn)))
+            },
+            Event::ControllerButtonDown { button, .. } => Some(Binding::new(Trigger::Pad(button))),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [(self.modifiers.ctrl, "Ctrl+"), (self.modifiers.shift, "Shift+"),
+                         (self.modifiers.alt, "Alt+"), (self.modifiers.gui, "Gui+")];
+        for &(held, name) in &modifiers {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        match self.trigger {
+            Trigger::Key(keycode) => write!(f, "{}", keycode.name()),
+            Trigger::Scancode(scancode) => write!(f, "scancode:{}", scancode.name()),
+            Trigger::Mouse(button) => {
+                let name = MOUSE_BUTTONS.iter()
+                    .find(|&&(_, b)| b == button)
+                    .map_or("Unknown", |&(name, _)| name);
+                write!(f, "mouse:{}", name)
+            },
+            Trigger::Pad(button) => write!(f, "pad:{}", button.string()),
+        }
+    }
+}
+
+/// Actions, by name, and what they are bound to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputMap {
+    bindings: BTreeMap<String, Vec<Binding>>,
+    // The modifiers held as of the last keyboard event, for mouse
+    // bindings (SDL's mouse events don't say)
+    modifiers: Modifiers,
+    allow_repeat: bool,
+}
+
+impl InputMap {
+
+    pub fn new() -> InputMap {
+        InputMap::default()
+    }
+
+    /// Parse the contents of a bindings file.
+    pub fn parse(text: &str) -> ::std::result::Result<InputMap, String> {
+        let file: BTreeMap<String, Vec<String>> = ron::from_str(text)
+            .map_err(|err| err.to_string())?;
+        let mut map = InputMap::new();
+        for (action, bindings) in file {
+            let bindings = bindings.iter()
+                .map(|binding| Binding::parse(binding)
+                     .map_err(|err| format!("{} (bound to '{}')", err, action)))
+                .collect::<::std::result::Result<Vec<_>, _>>()?;
+            map.bindings.insert(action, bindings);
+        }
+        Ok(map)
+    }
+
+    /// Load a lesson's default bindings from `resources/`.
+    pub fn load(path: &Path) -> Result<InputMap> {
+        let text = resources::read_to_string(path)?;
+        InputMap::parse(&text).map_err(|err| Error::data(path, err))
+    }
+
+    /// Load the bindings in `path`, a file of the player's own, over
+    /// the top of these.  Every action in it must already be known.
+    pub fn load_overrides(&mut self, path: &Path) -> Result<()> {
+        let text = fs::read_to_string(path).map_err(|err| Error::data(path, err))?;
+        InputMap::parse(&text)
+            .and_then(|overrides| self.merge(overrides))
+            .map_err(|err| Error::data(path, err))
+    }
+
+    /// Replace the bindings of each action in `overrides` with its own.
+    pub fn merge(&mut self, overrides: InputMap) -> ::std::result::Result<(), String> {
+        for (action, bindings) in overrides.bindings {
+            if !self.bindings.contains_key(&action) {
+                let known: Vec<&str> = self.actions().collect();
+                return Err(format!("unknown action '{}' (expected one of {})",
+                                   action, known.join(", ")));
+            }
+            self.bindings.insert(action, bindings);
+        }
+        Ok(())
+    }
+
+    /// The names of every action.
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(|action| action.as_str())
+    }
+
+    /// What `action` is bound to.
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.bindings.get(action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    /// Whether key repeats trigger actions again; off by default, so
+    /// holding a key down does its action once.
+    pub fn set_repeat(&mut self, allow_repeat: bool) {
+        self.allow_repeat = allow_repeat;
+    }
+
+    /// Add another binding for `action`.
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        self.bindings.entry(action.to_string()).or_default().push(binding);
+    }
+
+    /// Bind `action` to `binding` alone, dropping its other bindings.
+    pub fn rebind(&mut self, action: &str, binding: Binding) {
+        self.bindings.insert(action.to_string(), vec![binding]);
+    }
+
+    /// Leave `action` with nothing bound to it.
+    pub fn unbind(&mut self, action: &str) {
+        if let Some(bindings) = self.bindings.get_mut(action) {
+            bindings.clear();
+        }
+    }
+
+    /// The actions `event` triggers: usually none or one, but a key
+    /// can be bound to more than one action.  Keys fire when they go
+    /// down (and on key repeats, if `set_repeat` allows them), and
+    /// mouse and pad buttons on every press.  Like mouse buttons, pad
+    /// buttons go by the last modifiers seen on the keyboard.
+    pub fn triggered(&mut self, event: &Event) -> Vec<String> {
+        let (keycode, scancode, button, pad_button) = match *event {
+            Event::KeyDown { keycode, scancode, keymod, repeat, .. } => {
+                self.modifiers = Modifiers::from_mod(keymod);
+                if repeat && !self.allow_repeat {
+                    return Vec::new();
+                }
+                (keycode, scancode, None, None)
+            },
+            Event::KeyUp { keymod, .. } => {
+                self.modifiers = Modifiers::from_mod(keymod);
+                return Vec::new();
+            },
+            Event::MouseButtonDown { mouse_btn, .. } => (None, None, Some(mouse_btn), None),
+            Event::ControllerButtonDown { button, .. } => (None, None, None, Some(button)),
+            _ => return Vec::new(),
+        };
+        let modifiers = self.modifiers;
+        let matches = |binding: &Binding| {
+            binding.modifiers == modifiers && match binding.trigger {
+                Trigger::Key(key) => keycode == Some(key),
+                Trigger::Scancode(code) => scancode == Some(code),
+                Trigger::Mouse(b) => button == Some(b),
+                Trigger::Pad(b) => pad_button == Some(b),
+            }
+        };
+        self.bindings.iter()
+            .filter(|&(_, bindings)| bindings.iter().any(&matches))
+            .map(|(action, _)| action.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_down(keycode: Keycode, scancode: Scancode, keymod: Mod) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode),
+                         scancode: Some(scancode), keymod, repeat: false }
+    }
+
+    fn click(mouse_btn: MouseButton) -> Event {
+        Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn, x: 0, y: 0 }
+    }
+
+    #[test]
+    fn bindings_round_trip() {
+        for text in &["Q", "Ctrl+Shift+Escape", "scancode:W", "Alt+mouse:Right", "Keypad +",
+                     "pad:start"] {
+            assert_eq!(Binding::parse(text).unwrap().to_string(), *text);
+        }
+        let binding = Binding::parse("ctrl+q").unwrap();
+        assert_eq!(binding.trigger, Trigger::Key(Keycode::Q));
+        assert!(binding.modifiers.ctrl);
+        assert!(Binding::parse("Hyper+Q").is_err());
+        assert!(Binding::parse("mouse:Thumb").is_err());
+        assert!(Binding::parse("pad:turbo").is_err());
+        assert!(Binding::parse("Ctrl+").is_err());
+    }
+
+    #[test]
+    fn keycodes_follow_the_layout_and_scancodes_the_position() {
+        let mut map = InputMap::parse(r#"{ "by_label": ["Q"], "by_position": ["scancode:Q"] }"#)
+            .unwrap();
+        // The top left letter on an AZERTY keyboard is A
+        let azerty_a = key_down(Keycode::A, Scancode::Q, keyboard::NOMOD);
+        assert_eq!(map.triggered(&azerty_a), vec!["by_position"]);
+        let azerty_q = key_down(Keycode::Q, Scancode::A, keyboard::NOMOD);
+        assert_eq!(map.triggered(&azerty_q), vec!["by_label"]);
+    }
+
+    #[test]
+    fn modifiers_must_match_exactly() {
+        let mut map = InputMap::parse(r#"{ "quit": ["Ctrl+Q"], "tint": ["Q"] }"#).unwrap();
+        assert_eq!(map.triggered(&key_down(Keycode::Q, Scancode::Q, keyboard::NOMOD)),
+                   vec!["tint"]);
+        assert_eq!(map.triggered(&key_down(Keycode::Q, Scancode::Q, keyboard::RCTRLMOD)),
+                   vec!["quit"]);
+        // Caps lock doesn't count
+        assert_eq!(map.triggered(&key_down(Keycode::Q, Scancode::Q, keyboard::CAPSMOD)),
+                   vec!["tint"]);
+    }
+
+    #[test]
+    fn mouse_buttons_use_the_last_modifiers_seen() {
+        let mut map = InputMap::parse(r#"{ "fire": ["mouse:Left"], "alt_fire": ["Shift+mouse:Left"] }"#)
+            .unwrap();
+        assert_eq!(map.triggered(&click(MouseButton::Left)), vec!["fire"]);
+        map.triggered(&key_down(Keycode::LShift, Scancode::LShift, keyboard::LSHIFTMOD));
+        assert_eq!(map.triggered(&click(MouseButton::Left)), vec!["alt_fire"]);
+        assert!(map.triggered(&click(MouseButton::Right)).is_empty());
+    }
+
+    #[test]
+    fn pad_buttons_trigger_actions() {
+        let mut map = InputMap::parse(r#"{ "jump": ["Space", "pad:a"] }"#).unwrap();
+        let press = |button| Event::ControllerButtonDown { timestamp: 0, which: 0, button };
+        assert_eq!(map.triggered(&press(Button::A)), vec!["jump"]);
+        assert!(map.triggered(&press(Button::B)).is_empty());
+        assert!(map.triggered(&Event::ControllerButtonUp { timestamp: 0, which: 0,
+                                                           button: Button::A }).is_empty());
+    }
+
+    #[test]
+    fn overrides_replace_bindings_and_reject_unknown_actions() {
+        let mut map = InputMap::parse(r#"{ "quit": ["Escape", "Q"], "tint": ["W"] }"#).unwrap();
+        map.merge(InputMap::parse(r#"{ "quit": ["F10"] }"#).unwrap()).unwrap();
+        assert_eq!(map.bindings("quit"), &[Binding::new(Trigger::Key(Keycode::F10))]);
+        assert_eq!(map.bindings("tint"), &[Binding::new(Trigger::Key(Keycode::W))]);
+        let err = map.merge(InputMap::parse(r#"{ "qiut": ["F10"] }"#).unwrap()).unwrap_err();
+        assert!(err.contains("qiut"), "{}", err);
+    }
+
+    #[test]
+    fn actions_can_be_rebound_at_runtime() {
+        let mut map = InputMap::parse(r#"{ "quit": ["Escape"] }"#).unwrap();
+        let f10 = key_down(Keycode::F10, Scancode::F10, keyboard::NOMOD);
+        map.rebind("quit", Binding::from_event(&f10).unwrap());
+        assert_eq!(map.triggered(&f10), vec!["quit"]);
+        map.bind("quit", Binding::new(Trigger::Key(Keycode::Escape)));
+        assert_eq!(map.bindings("quit").len(), 2);
+        map.unbind("quit");
+        assert!(map.triggered(&f10).is_empty());
+    }
+
+    #[test]
+    fn key_repeats_only_count_when_asked_for() {
+        let mut map = InputMap::parse(r#"{ "tint": ["Q"] }"#).unwrap();
+        let repeat = Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(Keycode::Q),
+                                      scancode: Some(Scancode::Q), keymod: keyboard::NOMOD,
+                                      repeat: true };
+        assert!(map.triggered(&repeat).is_empty());
+        map.set_repeat(true);
+        assert_eq!(map.triggered(&repeat), vec!["tint"]);
+    }
+
+    // The bindings the lessons ship with all parse
+    #[test]
+    fn shipped_bindings_parse() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for lesson in &["lesson04", "lesson12", "lesson13", "lesson15", "lesson19", "lesson35",
+                        "lesson36", "lesson37", "lesson54", "lesson55"] {
+            let path = root.join("resources").join(format!("{}.keys.ron", lesson));
+            let text = fs::read_to_string(&path).unwrap();
+            if let Err(err) = InputMap::parse(&text) {
+                panic!("{}: {}", path.display(), err);
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Which keys went down, stayed down or came up since the last frame.
+//!
+//! `EventPump::keyboard_state()` only says whether a key is down right
+//! now, so on its own it can't tell a fresh press from a key that has
+//! been held for a second.  `KeyboardTracker` keeps the snapshot from
+//! the frame before to compare against, and the time each key went
+//! down.
+//!
+//! Each frame, pass every event to `handle_event` while draining the
+//! event pump, then call `update` once:
+//!
+//! ```text
+//! for event in event_pump.poll_iter() {
+//!     keys.handle_event(&event);
+//!     ...
+//! }
+//! keys.update(&event_pump.keyboard_state());
+//! if keys.just_pressed(Scancode::Space) { ... }
+//! ```
+//!
+//! The events catch taps too short to show up in a snapshot, and key
+//! repeats: holding a key down makes the OS send `KeyDown` again every
+//! so often, which is ignored unless `set_repeat(true)` is called, in
+//! which case each repeat counts as another press.
+//!
+//! Input played back with `--replay` only arrives as events, and never
+//! reaches the keyboard state.  Calling `update_from_events` instead of
+//! `update` works out which keys are down from the `KeyDown` and
+//! `KeyUp` events alone, so a replay drives the tracker too.  A key let
+//! go while another window has the focus sends no `KeyUp`, so losing
+//! the focus counts as letting go of everything.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{KeyboardState, Scancode};
+
+/// The keyboard this frame, compared with the last.
+#[derive(Clone, Debug)]
+pub struct KeyboardTracker {
+    // Keys down in this frame's snapshot and the last one
+    current: HashSet<Scancode>,
+    previous: HashSet<Scancode>,
+    // When each key that is down went down
+    down_since: HashMap<Scancode, Instant>,
+    // How long each key released this frame had been held
+    released_after: HashMap<Scancode, Duration>,
+    // Presses and repeats from events, gathered until the next update
+    pending_presses: HashSet<Scancode>,
+    pending_repeats: HashSet<Scancode>,
+    // ... and the ones that belong to this frame
+    presses: HashSet<Scancode>,
+    repeats: HashSet<Scancode>,
+    // Keys down according to the events seen so far
+    down: HashSet<Scancode>,
+    allow_repeat: bool,
+    now: Instant,
+}
+
+impl KeyboardTracker {
+
+    pub fn new() -> KeyboardTracker {
+        KeyboardTracker {
+            current: HashSet::new(),
+            previous: HashSet::new(),
+            down_since: HashMap::new(),
+            released_after: HashMap::new(),
+            pending_presses: HashSet::new(),
+            pending_repeats: HashSet::new(),
+            presses: HashSet::new(),
+            repeats: HashSet::new(),
+            down: HashSet::new(),
+            allow_repeat: false,
+            now: Instant::now(),
+        }
+    }
+
+    /// Whether key repeats count as presses; off by default.
+    pub fn set_repeat(&mut self, allow_repeat: bool) {
+        self.allow_repeat = allow_repeat;
+    }
+
+    /// Note a key press from the event queue, so that it is seen even
+    /// if the key is let go again before the next snapshot.
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown { scancode: Some(scancode), repeat, .. } => {
+                if repeat {
+                    self.pending_repeats.insert(scancode);
+                } else {
+                    self.pending_presses.insert(scancode);
+                }
+                self.down.insert(scancode);
+            },
+            Event::KeyUp { scancode: Some(scancode), .. } => {
+                self.down.remove(&scancode);
+            },
+            Event::Window { win_event: WindowEvent::FocusLost, .. } => {
+                self.down.clear();
+            },
+            _ => {},
+        }
+    }
+
+    /// Start a new frame from the current state of the keyboard.
+    pub fn update(&mut self, keys: &KeyboardState) {
+        self.update_from(keys.pressed_scancodes(), Instant::now());
+    }
+
+    /// As `update`, with the keys that are down worked out from the
+    /// events passed to `handle_event` rather than the keyboard state.
+    pub fn update_from_events(&mut self) {
+        let down: Vec<Scancode> = self.down.iter().cloned().collect();
+        self.update_from(down, Instant::now());
+    }
+
+    /// As `update`, from a list of the keys that are down at `now`.
+    pub fn update_from<I: IntoIterator<Item = Scancode>>(&mut self, pressed: I, now: Instant) {
+        self.previous = ::std::mem::replace(&mut self.current, pressed.into_iter().collect());
+        self.presses = ::std::mem::take(&mut self.pending_presses);
+        self.repeats = ::std::mem::take(&mut self.pending_repeats);
+        self.now = now;
+
+        self.released_after.clear();
+        for scancode in self.previous.difference(&self.current) {
+            if let Some(since) = self.down_since.remove(scancode) {
+                self.released_after.insert(*scancode, now - since);
+            }
+        }
+        for &scancode in self.current.iter().chain(&self.presses) {
+            self.down_since.entry(scancode).or_insert(now);
+        }
+        // A tap that was over by the time of the snapshot
+        for scancode in self.presses.difference(&self.current) {
+            self.down_since.remove(scancode);
+            self.released_after.insert(*scancode, Duration::from_secs(0));
+        }
+    }
+
+    /// True if `scancode` went down since the last frame (or repeated,
+    /// with repeats turned on).
+    pub fn just_pressed(&self, scancode: Scancode) -> bool {
+        (self.current.contains(&scancode) && !self.previous.contains(&scancode)) ||
+            self.presses.contains(&scancode) ||
+            (self.allow_repeat && self.repeats.contains(&scancode))
+    }
+
+    /// True if `scancode` is down.
+    pub fn held(&self, scancode: Scancode) -> bool {
+        self.current.contains(&scancode)
+    }
+
+    /// True if `scancode` came up since the last frame.
+    pub fn just_released(&self, scancode: Scancode) -> bool {
+        self.released_after.contains_key(&scancode)
+    }
+
+    /// How long `scancode` has been held down, or, in the frame it is
+    /// released, how long it was held for.  `None` otherwise.
+    pub fn held_for(&self, scancode: Scancode) -> Option<Duration> {
+        match self.down_since.get(&scancode) {
+            Some(&since) => Some(self.now - since),
+            None => self.released_after.get(&scancode).cloned(),
+        }
+    }
+}
+
+impl Default for KeyboardTracker {
+    fn default() -> KeyboardTracker {
+        KeyboardTracker::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::{self, Keycode};
+
+    fn key_down(scancode: Scancode, repeat: bool) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(Keycode::A),
+                         scancode: Some(scancode), keymod: keyboard::NOMOD, repeat }
+    }
+
+    #[test]
+    fn press_hold_release() {
+        let start = Instant::now();
+        let frame = Duration::from_millis(16);
+        let mut keys = KeyboardTracker::new();
+
+        keys.update_from(vec![Scancode::A], start);
+        assert!(keys.just_pressed(Scancode::A));
+        assert!(keys.held(Scancode::A));
+        assert_eq!(keys.held_for(Scancode::A), Some(Duration::from_secs(0)));
+
+        keys.update_from(vec![Scancode::A], start + frame);
+        assert!(!keys.just_pressed(Scancode::A));
+        assert!(keys.held(Scancode::A));
+        assert_eq!(keys.held_for(Scancode::A), Some(frame));
+
+        keys.update_from(vec![], start + frame * 2);
+        assert!(keys.just_released(Scancode::A));
+        assert!(!keys.held(Scancode::A));
+        assert_eq!(keys.held_for(Scancode::A), Some(frame * 2));
+
+        keys.update_from(vec![], start + frame * 3);
+        assert!(!keys.just_released(Scancode::A));
+        assert_eq!(keys.held_for(Scancode::A), None);
+    }
+
+    #[test]
+    fn repeats_are_ignored_unless_asked_for() {
+        let start = Instant::now();
+        let mut keys = KeyboardTracker::new();
+        keys.update_from(vec![Scancode::A], start);
+        keys.handle_event(&key_down(Scancode::A, true));
+        keys.update_from(vec![Scancode::A], start);
+        assert!(!keys.just_pressed(Scancode::A));
+
+        keys.set_repeat(true);
+        keys.handle_event(&key_down(Scancode::A, true));
+        keys.update_from(vec![Scancode::A], start);
+        assert!(keys.just_pressed(Scancode::A));
+    }
+
+    #[test]
+    fn events_alone_can_drive_the_tracker() {
+        let mut keys = KeyboardTracker::new();
+        keys.handle_event(&key_down(Scancode::A, false));
+        keys.update_from_events();
+        assert!(keys.just_pressed(Scancode::A));
+        assert!(keys.held(Scancode::A));
+
+        keys.update_from_events();
+        assert!(keys.held(Scancode::A));
+        assert!(!keys.just_pressed(Scancode::A));
+
+        keys.handle_event(&Event::KeyUp { timestamp: 0, window_id: 0, keycode: Some(Keycode::A),
+                                          scancode: Some(Scancode::A), keymod: keyboard::NOMOD,
+                                          repeat: false });
+        keys.update_from_events();
+        assert!(keys.just_released(Scancode::A));
+        assert!(!keys.held(Scancode::A));
+    }
+
+    #[test]
+    fn losing_focus_lets_go_of_every_key() {
+        let mut keys = KeyboardTracker::new();
+        keys.handle_event(&key_down(Scancode::A, false));
+        keys.update_from_events();
+        keys.handle_event(&Event::Window { timestamp: 0, window_id: 1,
+                                           win_event: WindowEvent::FocusLost });
+        keys.update_from_events();
+        assert!(keys.just_released(Scancode::A));
+        assert!(!keys.held(Scancode::A));
+    }
+
+    #[test]
+    fn taps_between_snapshots_are_not_lost() {
+        let mut keys = KeyboardTracker::new();
+        keys.handle_event(&key_down(Scancode::Space, false));
+        keys.update_from(vec![], Instant::now());
+        assert!(keys.just_pressed(Scancode::Space));
+        assert!(keys.just_released(Scancode::Space));
+        assert!(!keys.held(Scancode::Space));
+    }
+}
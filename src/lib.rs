@@ -0,0 +1,31 @@
+//! Code shared between the lesson ports.
+//!
+//! The lessons started out self-contained, with each one carrying its
+//! own copy of the helpers it needed.  Anything that more than one
+//! lesson uses now lives here, so fixes only have to be made once.
+
+extern crate ron;
+extern crate sdl2;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+pub mod animation;
+pub mod assets;
+pub mod bitmap_font;
+pub mod button;
+pub mod capture;
+pub mod display;
+pub mod error;
+pub mod gamepad;
+pub mod input;
+pub mod keyboard;
+pub mod options;
+pub mod replay;
+pub mod resources;
+pub mod sprites;
+pub mod textbox;
+pub mod texture;
+pub mod tiles;
+pub mod touch;
+pub mod window;
@@ -0,0 +1,303 @@
+//! Command line options understood by every lesson.
+//!
+//! The lessons were written for a 640x480 window on a desktop with
+//! OpenGL, which is no good on a build server with no window manager,
+//! or for anyone who wants a bigger window.  Each lesson's `main` parses
+//! the flags below and builds its window and renderer through
+//! `Options`.  The lessons keep drawing in their own 640x480
+//! coordinates; if the window is a different size, SDL scales the
+//! picture (and the mouse coordinates) to fit.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use sdl2::VideoSubsystem;
+use sdl2::video::Window;
+use sdl2::render::Renderer;
+
+use capture::FrameCapture;
+use error::{Error, Result};
+use input::InputMap;
+use replay::EventSource;
+
+const USAGE: &str = "\
+Options:
+    --width N        window width in pixels
+    --height N       window height in pixels
+    --fullscreen     fill the screen instead of opening a window
+    --vsync          wait for vertical sync before each new frame
+    --software       use SDL's software renderer
+    --accelerated    use a hardware accelerated renderer
+    --title TEXT     window title
+    --frames N       quit after drawing N frames
+    --capture FILE   save the last frame (see --frames) as a PNG
+    --bindings FILE  load key bindings from FILE over the lesson's own
+    --record FILE    save every input event to FILE
+    --replay FILE    play back the events saved by --record
+    --help           show this message";
+
+/// Which of SDL's renderers to ask for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderMode {
+    /// Let SDL pick, preferring hardware acceleration
+    Auto,
+    Software,
+    Accelerated,
+}
+
+/// The settings every lesson accepts on its command line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// Window size; each lesson supplies its own default
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub render_mode: RenderMode,
+    /// Replaces the lesson's own window title
+    pub title: Option<String>,
+    /// Number of frames to draw before quitting, for scripted runs
+    pub frames: Option<u32>,
+    /// Where to save the last frame
+    pub capture: Option<PathBuf>,
+    /// Key bindings to use in place of the lesson's defaults
+    pub bindings: Option<PathBuf>,
+    /// Where to save the input events
+    pub record: Option<PathBuf>,
+    /// Recorded input events to use in place of live input
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            width: None,
+            height: None,
+            fullscreen: false,
+            vsync: false,
+            render_mode: RenderMode::Auto,
+            title: None,
+            frames: None,
+            capture: None,
+            bindings: None,
+            record: None,
+            replay: None,
+        }
+    }
+}
+
+impl Options {
+
+    /// Parse the program's command line.  `--help` prints the list of
+    /// options and exits.
+    pub fn from_args() -> Result<Options> {
+        let args: Vec<String> = env::args().skip(1).collect();
+        if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        Options::parse(args)
+    }
+
+    /// Parse a list of arguments, not including the program name.
+    /// Values can be given as `--width 800` or `--width=800`.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Split off an inline "=value", if there is one
+            let (flag, mut inline) = match arg.find('=') {
+                Some(at) => (arg[..at].to_string(), Some(arg[at + 1..].to_string())),
+                None => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline.take().or_else(|| args.next())
+                    .ok_or_else(|| Error::Usage(format!("{} needs a value", flag)))
+            };
+            match flag.as_str() {
+                "--width" => options.width = Some(parse_size("--width", &value()?)?),
+                "--height" => options.height = Some(parse_size("--height", &value()?)?),
+                "--title" => options.title = Some(value()?),
+                "--frames" => options.frames = Some(parse_size("--frames", &value()?)?),
+                "--capture" => options.capture = Some(PathBuf::from(value()?)),
+                "--bindings" => options.bindings = Some(PathBuf::from(value()?)),
+                "--record" => options.record = Some(PathBuf::from(value()?)),
+                "--replay" => options.replay = Some(PathBuf::from(value()?)),
+                "--fullscreen" => options.fullscreen = true,
+                "--vsync" => options.vsync = true,
+                "--software" => options.render_mode = RenderMode::Software,
+                "--accelerated" => options.render_mode = RenderMode::Accelerated,
+                _ => return Err(Error::Usage(format!("unknown option {}", arg))),
+            }
+            if inline.is_some() {
+                return Err(Error::Usage(format!("{} does not take a value", flag)));
+            }
+        }
+        if options.record.is_some() && options.replay.is_some() {
+            return Err(Error::Usage("--record and --replay can't be used together".to_string()));
+        }
+        Ok(options)
+    }
+
+    /// Create the lesson's window.  `title`, `width` and `height` are
+    /// the lesson's defaults, used unless the command line overrides
+    /// them.
+    pub fn window(&self, video: &VideoSubsystem, title: &str, width: u32, height: u32)
+                  -> Result<Window> {
+        self.build_window(video, title, width, height, false)
+    }
+
+    /// As `window`, for a lesson that lays itself out to fit: the
+    /// window can be resized.  Pair it with `native_renderer`.
+    pub fn resizable_window(&self, video: &VideoSubsystem, title: &str, width: u32, height: u32)
+                            -> Result<Window> {
+        self.build_window(video, title, width, height, true)
+    }
+
+    fn build_window(&self, video: &VideoSubsystem, title: &str, width: u32, height: u32,
+                    resizable: bool) -> Result<Window> {
+        let title = self.title.as_ref().map_or(title, |title| title.as_str());
+        let mut builder = video.window(title,
+                                       self.width.unwrap_or(width),
+                                       self.height.unwrap_or(height));
+        if self.fullscreen {
+            builder.fullscreen_desktop();
+        } else {
+            builder.position_centered();
+        }
+        if resizable {
+            builder.resizable();
+        }
+        builder.build().map_err(|err| Error::init("window", err))
+    }
+
+    /// Create a renderer for `window`, which draws in a `width` x
+    /// `height` coordinate space whatever size the window really is.
+    pub fn renderer(&self, window: Window, width: u32, height: u32) -> Result<Renderer<'static>> {
+        let mut renderer = self.native_renderer(window)?;
+        renderer.set_logical_size(width, height)
+            .map_err(|err| Error::init("renderer", err))?;
+        Ok(renderer)
+    }
+
+    /// A renderer for `window` that draws in the window's own pixels,
+    /// for lessons that lay themselves out to fit the window rather
+    /// than being scaled to it.
+    pub fn native_renderer(&self, window: Window) -> Result<Renderer<'static>> {
+        let mut builder = window.renderer();
+        match self.render_mode {
+            RenderMode::Auto => {},
+            RenderMode::Software => builder = builder.software(),
+            RenderMode::Accelerated => builder = builder.accelerated(),
+        }
+        if self.vsync {
+            builder = builder.present_vsync();
+        }
+        builder.build().map_err(|err| Error::init("renderer", err))
+    }
+
+    /// Just the options that say how to show a window: its size,
+    /// fullscreen, vsync, the renderer and the title.  Frame counts,
+    /// captures, recordings and key bindings belong to one run of one
+    /// lesson, so they are left out.
+    pub fn display_options(&self) -> Options {
+        Options {
+            width: self.width,
+            height: self.height,
+            fullscreen: self.fullscreen,
+            vsync: self.vsync,
+            render_mode: self.render_mode,
+            title: self.title.clone(),
+            ..Options::default()
+        }
+    }
+
+    /// A frame counter that honours `--frames` and `--capture`.
+    pub fn frame_capture(&self) -> FrameCapture {
+        FrameCapture::new(self.frames, self.capture.clone())
+    }
+
+    /// Where the lesson's events come from: live, recorded with
+    /// `--record`, or played back with `--replay`.
+    pub fn event_source(&self) -> Result<EventSource> {
+        if let Some(ref path) = self.record {
+            Ok(EventSource::record(path))
+        } else if let Some(ref path) = self.replay {
+            EventSource::replay(path)
+        } else {
+            Ok(EventSource::live())
+        }
+    }
+
+    /// The lesson's key bindings: `defaults`, with the file given by
+    /// `--bindings` (if any) loaded over the top.
+    pub fn input_map(&self, defaults: &str) -> Result<InputMap> {
+        let mut map = InputMap::load(Path::new(defaults))?;
+        if let Some(ref path) = self.bindings {
+            map.load_overrides(path)?;
+        }
+        Ok(map)
+    }
+}
+
+// Sizes and frame counts must be positive whole numbers
+fn parse_size(flag: &str, value: &str) -> Result<u32> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(Error::Usage(format!("{} expects a positive number, not '{}'", flag, value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_gives_the_defaults() {
+        assert_eq!(parse(&[]).unwrap(), Options::default());
+    }
+
+    #[test]
+    fn values_can_be_separate_or_inline() {
+        let options = parse(&["--width", "800", "--height=600", "--title", "Big",
+                              "--bindings=azerty.ron"]).unwrap();
+        assert_eq!(options.width, Some(800));
+        assert_eq!(options.height, Some(600));
+        assert_eq!(options.title, Some("Big".to_string()));
+        assert_eq!(options.bindings, Some(PathBuf::from("azerty.ron")));
+    }
+
+    #[test]
+    fn switches() {
+        let options = parse(&["--fullscreen", "--vsync", "--software", "--frames", "3"]).unwrap();
+        assert!(options.fullscreen);
+        assert!(options.vsync);
+        assert_eq!(options.render_mode, RenderMode::Software);
+        assert_eq!(options.frames, Some(3));
+    }
+
+    #[test]
+    fn display_options_leave_out_per_run_files() {
+        let options = parse(&["--width", "800", "--software", "--frames", "3",
+                              "--capture", "last.png", "--record", "input.ron"]).unwrap();
+        let display = options.display_options();
+        assert_eq!(display.width, Some(800));
+        assert_eq!(display.render_mode, RenderMode::Software);
+        assert_eq!((display.frames, display.capture, display.record), (None, None, None));
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert!(parse(&["--bogus"]).is_err());
+        assert!(parse(&["--width"]).is_err());
+        assert!(parse(&["--width", "wide"]).is_err());
+        assert!(parse(&["--frames", "0"]).is_err());
+        assert!(parse(&["--vsync=yes"]).is_err());
+        assert!(parse(&["--record", "a.ron", "--replay", "b.ron"]).is_err());
+    }
+}
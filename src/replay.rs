@@ -0,0 +1,680 @@
+//! Recording a lesson's input, and playing it back.
+//!
+//! Lessons take their events from an `EventSource` rather than straight
+//! from the event pump:
+//!
+//! ```text
+//! let mut events = options.event_source()?;
+//! while running {
+//!     for event in events.poll(&mut event_pump) { ... }
+//!     ...
+//! }
+//! events.finish()?;
+//! ```
+//!
+//! Normally that hands on whatever the pump has.  With `--record FILE`
+//! the events are also written down, along with the frame each arrived
+//! in, and saved to `FILE` by `finish`.  With `--replay FILE` the live
+//! input is thrown away and the events in `FILE` are handed out
+//! instead, each on the same frame it was recorded on, so the lesson
+//! sees exactly the same input as it did then.  Together with
+//! `--frames` and `--capture` this makes scripted demos that run
+//! headless.
+//!
+//! Two kinds of live event still get through a replay: `Quit` and
+//! window events, so the windows can still be closed (and hidden, or
+//! resized), and events the lesson made itself.  A lesson that pushes
+//! events onto SDL's queue does it with `push`, so that they can be
+//! told apart from real input.  They aren't recorded either, since the
+//! lesson makes them again from the replayed input.
+//!
+//! Keyboard, mouse, text, window, game controller, joystick, touch and
+//! multi-finger gesture events are recorded.  Lessons
+//! that read the keyboard or mouse state straight from the event pump
+//! don't see replayed input that way; `KeyboardTracker` can follow the
+//! events instead (see `keyboard`).
+//!
+//! A recording is also saved when the `EventSource` is dropped, so a
+//! lesson that stops early with an error still leaves its input behind
+//! to replay.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use ron;
+use sdl2::{EventPump, EventSubsystem};
+use sdl2::controller::{Axis, Button};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::mouse::{MouseButton, MouseState, MouseWheelDirection};
+
+use error::{Error, Result};
+
+/// `sdl2::event::WindowEvent`, in a form that can be saved.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WindowChange {
+    Shown,
+    Hidden,
+    Exposed,
+    Moved(i32, i32),
+    Resized(i32, i32),
+    SizeChanged(i32, i32),
+    Minimized,
+    Maximized,
+    Restored,
+    Enter,
+    Leave,
+    FocusGained,
+    FocusLost,
+    Close,
+}
+
+// Game controller axes and buttons are saved by name
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Axis")]
+enum AxisName {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    TriggerLeft,
+    TriggerRight,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Button")]
+enum ButtonName {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// An event, as it is saved in a recording.  Keys, scancodes, modifiers
+/// and mouse and joystick buttons are stored as SDL's numbers for them;
+/// game controller axes and buttons by name.  `which` is a joystick's
+/// device index in the `DeviceAdded` events, and its instance id in
+/// the rest.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    Quit,
+    Window { window_id: u32, change: WindowChange },
+    KeyDown { window_id: u32, keycode: Option<i32>, scancode: Option<i32>, keymod: u16, repeat: bool },
+    KeyUp { window_id: u32, keycode: Option<i32>, scancode: Option<i32>, keymod: u16, repeat: bool },
+    TextEditing { window_id: u32, text: String, start: i32, length: i32 },
+    TextInput { window_id: u32, text: String },
+    MouseMotion { window_id: u32, which: u32, state: u32, x: i32, y: i32, xrel: i32, yrel: i32 },
+    MouseButtonDown { window_id: u32, which: u32, button: u8, x: i32, y: i32 },
+    MouseButtonUp { window_id: u32, which: u32, button: u8, x: i32, y: i32 },
+    MouseWheel { window_id: u32, which: u32, x: i32, y: i32, flipped: bool },
+    JoyAxisMotion { which: i32, axis: u8, value: i16 },
+    JoyButtonDown { which: i32, button: u8 },
+    JoyButtonUp { which: i32, button: u8 },
+    JoyDeviceAdded { which: i32 },
+    JoyDeviceRemoved { which: i32 },
+    ControllerAxisMotion { which: i32, #[serde(with = "AxisName")] axis: Axis, value: i16 },
+    ControllerButtonDown { which: i32, #[serde(with = "ButtonName")] button: Button },
+    ControllerButtonUp { which: i32, #[serde(with = "ButtonName")] button: Button },
+    ControllerDeviceAdded { which: i32 },
+    ControllerDeviceRemoved { which: i32 },
+    ControllerDeviceRemapped { which: i32 },
+    FingerDown { touch_id: i64, finger_id: i64, x: f32, y: f32, dx: f32, dy: f32, pressure: f32 },
+    FingerUp { touch_id: i64, finger_id: i64, x: f32, y: f32, dx: f32, dy: f32, pressure: f32 },
+    FingerMotion { touch_id: i64, finger_id: i64, x: f32, y: f32, dx: f32, dy: f32, pressure: f32 },
+    MultiGesture { touch_id: i64, d_theta: f32, d_dist: f32, x: f32, y: f32, num_fingers: u16 },
+}
+
+impl WindowChange {
+
+    fn from_sdl(event: &WindowEvent) -> Option<WindowChange> {
+        Some(match *event {
+            WindowEvent::Shown => WindowChange::Shown,
+            WindowEvent::Hidden => WindowChange::Hidden,
+            WindowEvent::Exposed => WindowChange::Exposed,
+            WindowEvent::Moved(x, y) => WindowChange::Moved(x, y),
+            WindowEvent::Resized(w, h) => WindowChange::Resized(w, h),
+            WindowEvent::SizeChanged(w, h) => WindowChange::SizeChanged(w, h),
+            WindowEvent::Minimized => WindowChange::Minimized,
+            WindowEvent::Maximized => WindowChange::Maximized,
+            WindowEvent::Restored => WindowChange::Restored,
+            WindowEvent::Enter => WindowChange::Enter,
+            WindowEvent::Leave => WindowChange::Leave,
+            WindowEvent::FocusGained => WindowChange::FocusGained,
+            WindowEvent::FocusLost => WindowChange::FocusLost,
+            WindowEvent::Close => WindowChange::Close,
+            _ => return None,
+        })
+    }
+
+    fn to_sdl(self) -> WindowEvent {
+        match self {
+            WindowChange::Shown => WindowEvent::Shown,
+            WindowChange::Hidden => WindowEvent::Hidden,
+            WindowChange::Exposed => WindowEvent::Exposed,
+            WindowChange::Moved(x, y) => WindowEvent::Moved(x, y),
+            WindowChange::Resized(w, h) => WindowEvent::Resized(w, h),
+            WindowChange::SizeChanged(w, h) => WindowEvent::SizeChanged(w, h),
+            WindowChange::Minimized => WindowEvent::Minimized,
+            WindowChange::Maximized => WindowEvent::Maximized,
+            WindowChange::Restored => WindowEvent::Restored,
+            WindowChange::Enter => WindowEvent::Enter,
+            WindowChange::Leave => WindowEvent::Leave,
+            WindowChange::FocusGained => WindowEvent::FocusGained,
+            WindowChange::FocusLost => WindowEvent::FocusLost,
+            WindowChange::Close => WindowEvent::Close,
+        }
+    }
+}
+
+impl RecordedEvent {
+
+    /// The recordable part of `event`, or `None` for the kinds of event
+    /// that aren't recorded.
+    pub fn from_sdl(event: &Event) -> Option<RecordedEvent> {
+        Some(match *event {
+            Event::Quit { .. } => RecordedEvent::Quit,
+            Event::Window { window_id, ref win_event, .. } => RecordedEvent::Window {
+                window_id,
+                change: WindowChange::from_sdl(win_event)?,
+            },
+            Event::KeyDown { window_id, keycode, scancode, keymod, repeat, .. } => {
+                RecordedEvent::KeyDown {
+                    window_id,
+                    keycode: keycode.map(|keycode| keycode as i32),
+                    scancode: scancode.map(|scancode| scancode as i32),
+                    keymod: keymod.bits(),
+                    repeat,
+                }
+            },
+            Event::KeyUp { window_id, keycode, scancode, keymod, repeat, .. } => {
+                RecordedEvent::KeyUp {
+                    window_id,
+                    keycode: keycode.map(|keycode| keycode as i32),
+                    scancode: scancode.map(|scancode| scancode as i32),
+                    keymod: keymod.bits(),
+                    repeat,
+                }
+            },
+            Event::TextEditing { window_id, ref text, start, length, .. } => {
+                RecordedEvent::TextEditing { window_id, text: text.clone(), start, length }
+            },
+            Event::TextInput { window_id, ref text, .. } => {
+                RecordedEvent::TextInput { window_id, text: text.clone() }
+            },
+            Event::MouseMotion { window_id, which, mousestate, x, y, xrel, yrel, .. } => {
+                RecordedEvent::MouseMotion {
+                    window_id, which, state: mousestate.to_sdl_state(), x, y, xrel, yrel,
+                }
+            },
+            Event::MouseButtonDown { window_id, which, mouse_btn, x, y, .. } => {
+                RecordedEvent::MouseButtonDown { window_id, which, button: mouse_btn as u8, x, y }
+            },
+            Event::MouseButtonUp { window_id, which, mouse_btn, x, y, .. } => {
+                RecordedEvent::MouseButtonUp { window_id, which, button: mouse_btn as u8, x, y }
+            },
+            Event::MouseWheel { window_id, which, x, y, direction, .. } => {
+                RecordedEvent::MouseWheel {
+                    window_id, which, x, y, flipped: direction == MouseWheelDirection::Flipped,
+                }
+            },
+            Event::JoyAxisMotion { which, axis_idx, value, .. } => {
+                RecordedEvent::JoyAxisMotion { which, axis: axis_idx, value }
+            },
+            Event::JoyButtonDown { which, button_idx, .. } => {
+                RecordedEvent::JoyButtonDown { which, button: button_idx }
+            },
+            Event::JoyButtonUp { which, button_idx, .. } => {
+                RecordedEvent::JoyButtonUp { which, button: button_idx }
+            },
+            Event::JoyDeviceAdded { which, .. } => RecordedEvent::JoyDeviceAdded { which },
+            Event::JoyDeviceRemoved { which, .. } => RecordedEvent::JoyDeviceRemoved { which },
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                RecordedEvent::ControllerAxisMotion { which, axis, value }
+            },
+            Event::ControllerButtonDown { which, button, .. } => {
+                RecordedEvent::ControllerButtonDown { which, button }
+            },
+            Event::ControllerButtonUp { which, button, .. } => {
+                RecordedEvent::ControllerButtonUp { which, button }
+            },
+            Event::ControllerDeviceAdded { which, .. } => {
+                RecordedEvent::ControllerDeviceAdded { which }
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                RecordedEvent::ControllerDeviceRemoved { which }
+            },
+            Event::ControllerDeviceRemapped { which, .. } => {
+                RecordedEvent::ControllerDeviceRemapped { which }
+            },
+            Event::FingerDown { touch_id, finger_id, x, y, dx, dy, pressure, .. } => {
+                RecordedEvent::FingerDown { touch_id, finger_id, x, y, dx, dy, pressure }
+            },
+            Event::FingerUp { touch_id, finger_id, x, y, dx, dy, pressure, .. } => {
+                RecordedEvent::FingerUp { touch_id, finger_id, x, y, dx, dy, pressure }
+            },
+            Event::FingerMotion { touch_id, finger_id, x, y, dx, dy, pressure, .. } => {
+                RecordedEvent::FingerMotion { touch_id, finger_id, x, y, dx, dy, pressure }
+            },
+            Event::MultiGesture { touch_id, d_theta, d_dist, x, y, num_fingers, .. } => {
+                RecordedEvent::MultiGesture { touch_id, d_theta, d_dist, x, y, num_fingers }
+            },
+            _ => return None,
+        })
+    }
+
+    /// Turn the saved event back into an SDL one, stamped with
+    /// `timestamp`.
+    pub fn to_sdl(&self, timestamp: u32) -> Event {
+        let keycode = |code: Option<i32>| code.and_then(Keycode::from_i32);
+        let scancode = |code: Option<i32>| code.and_then(Scancode::from_i32);
+        match *self {
+            RecordedEvent::Quit => Event::Quit { timestamp },
+            RecordedEvent::Window { window_id, change } => Event::Window {
+                timestamp, window_id, win_event: change.to_sdl(),
+            },
+            RecordedEvent::KeyDown { window_id, keycode: k, scancode: s, keymod, repeat } => {
+                Event::KeyDown {
+                    timestamp, window_id, keycode: keycode(k), scancode: scancode(s),
+                    keymod: Mod::from_bits_truncate(keymod), repeat,
+                }
+            },
+            RecordedEvent::KeyUp { window_id, keycode: k, scancode: s, keymod, repeat } => {
+                Event::KeyUp {
+                    timestamp, window_id, keycode: keycode(k), scancode: scancode(s),
+                    keymod: Mod::from_bits_truncate(keymod), repeat,
+                }
+            },
+            RecordedEvent::TextEditing { window_id, ref text, start, length } => {
+                Event::TextEditing { timestamp, window_id, text: text.clone(), start, length }
+            },
+            RecordedEvent::TextInput { window_id, ref text } => {
+                Event::TextInput { timestamp, window_id, text: text.clone() }
+            },
+            RecordedEvent::MouseMotion { window_id, which, state, x, y, xrel, yrel } => {
+                Event::MouseMotion {
+                    timestamp, window_id, which,
+                    mousestate: MouseState::from_sdl_state(state), x, y, xrel, yrel,
+                }
+            },
+            RecordedEvent::MouseButtonDown { window_id, which, button, x, y } => {
+                Event::MouseButtonDown {
+                    timestamp, window_id, which, mouse_btn: MouseButton::from_ll(button), x, y,
+                }
+            },
+            RecordedEvent::MouseButtonUp { window_id, which, button, x, y } => {
+                Event::MouseButtonUp {
+                    timestamp, window_id, which, mouse_btn: MouseButton::from_ll(button), x, y,
+                }
+            },
+            RecordedEvent::MouseWheel { window_id, which, x, y, flipped } => {
+                let direction = if flipped {
+                    MouseWheelDirection::Flipped
+                } else {
+                    MouseWheelDirection::Normal
+                };
+                Event::MouseWheel { timestamp, window_id, which, x, y, direction }
+            },
+            RecordedEvent::JoyAxisMotion { which, axis, value } => {
+                Event::JoyAxisMotion { timestamp, which, axis_idx: axis, value }
+            },
+            RecordedEvent::JoyButtonDown { which, button } => {
+                Event::JoyButtonDown { timestamp, which, button_idx: button }
+            },
+            RecordedEvent::JoyButtonUp { which, button } => {
+                Event::JoyButtonUp { timestamp, which, button_idx: button }
+            },
+            RecordedEvent::JoyDeviceAdded { which } => Event::JoyDeviceAdded { timestamp, which },
+            RecordedEvent::JoyDeviceRemoved { which } => Event::JoyDeviceRemoved { timestamp, which },
+            RecordedEvent::ControllerAxisMotion { which, axis, value } => {
+                Event::ControllerAxisMotion { timestamp, which, axis, value }
+            },
+            RecordedEvent::ControllerButtonDown { which, button } => {
+                Event::ControllerButtonDown { timestamp, which, button }
+            },
+            RecordedEvent::ControllerButtonUp { which, button } => {
+                Event::ControllerButtonUp { timestamp, which, button }
+            },
+            RecordedEvent::ControllerDeviceAdded { which } => {
+                Event::ControllerDeviceAdded { timestamp, which }
+            },
+            RecordedEvent::ControllerDeviceRemoved { which } => {
+                Event::ControllerDeviceRemoved { timestamp, which }
+            },
+            RecordedEvent::ControllerDeviceRemapped { which } => {
+                Event::ControllerDeviceRemapped { timestamp, which }
+            },
+            RecordedEvent::FingerDown { touch_id, finger_id, x, y, dx, dy, pressure } => {
+                Event::FingerDown { timestamp, touch_id, finger_id, x, y, dx, dy, pressure }
+            },
+            RecordedEvent::FingerUp { touch_id, finger_id, x, y, dx, dy, pressure } => {
+                Event::FingerUp { timestamp, touch_id, finger_id, x, y, dx, dy, pressure }
+            },
+            RecordedEvent::FingerMotion { touch_id, finger_id, x, y, dx, dy, pressure } => {
+                Event::FingerMotion { timestamp, touch_id, finger_id, x, y, dx, dy, pressure }
+            },
+            RecordedEvent::MultiGesture { touch_id, d_theta, d_dist, x, y, num_fingers } => {
+                Event::MultiGesture { timestamp, touch_id, d_theta, d_dist, x, y, num_fingers }
+            },
+        }
+    }
+}
+
+/// The events that arrived during one frame.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    /// Counting from 0, the first call to `EventSource::poll`
+    pub frame: u32,
+    /// Milliseconds since recording started, for reading the file;
+    /// playback goes by frame number only
+    pub time_ms: u32,
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Everything recorded in one run.  Frames without any events are left
+/// out.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub frames: Vec<Frame>,
+}
+
+impl Recording {
+
+    pub fn parse(text: &str) -> ::std::result::Result<Recording, String> {
+        ron::from_str(text).map_err(|err| err.to_string())
+    }
+
+    pub fn to_text(&self) -> ::std::result::Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Recording> {
+        let text = fs::read_to_string(path).map_err(|err| Error::data(path, err))?;
+        Recording::parse(&text).map_err(|err| Error::data(path, err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = self.to_text().map_err(|err| Error::data(path, err))?;
+        fs::write(path, text).map_err(|err| Error::data(path, err))
+    }
+}
+
+enum Mode {
+    Live,
+    Record(PathBuf, Recording),
+    Replay(VecDeque<Frame>),
+}
+
+/// Where a lesson's events come from: see the module documentation.
+pub struct EventSource {
+    mode: Mode,
+    frame: u32,
+    start: Instant,
+    // Events pushed by the lesson that haven't come back round yet
+    pushed: Vec<RecordedEvent>,
+}
+
+impl EventSource {
+
+    fn new(mode: Mode) -> EventSource {
+        EventSource { mode, frame: 0, start: Instant::now(), pushed: Vec::new() }
+    }
+
+    /// Events straight from the event pump.
+    pub fn live() -> EventSource {
+        EventSource::new(Mode::Live)
+    }
+
+    /// Live events, also recorded to `path` by `finish`.
+    pub fn record(path: &Path) -> EventSource {
+        EventSource::new(Mode::Record(path.to_path_buf(), Recording::default()))
+    }
+
+    /// The events recorded in `path`, in place of live input.
+    pub fn replay(path: &Path) -> Result<EventSource> {
+        Ok(EventSource::replay_recording(Recording::load(path)?))
+    }
+
+    /// As `replay`, from a recording already in memory.
+    pub fn replay_recording(recording: Recording) -> EventSource {
+        EventSource::new(Mode::Replay(recording.frames.into()))
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replay(_))
+    }
+
+    /// Push an event the lesson made itself onto SDL's queue.  It comes
+    /// back from `poll` like any other, even while replaying.
+    pub fn push(&mut self, queue: &EventSubsystem, event: Event) -> Result<()> {
+        self.expect_pushed(&event);
+        queue.push_event(event).map_err(Error::event)
+    }
+
+    // Remember `event`, so that it is known to be the lesson's when it
+    // comes back from the event pump
+    fn expect_pushed(&mut self, event: &Event) {
+        if let Some(event) = RecordedEvent::from_sdl(event) {
+            self.pushed.push(event);
+        }
+    }
+
+    // Whether `event` is one the lesson pushed, forgetting it if so
+    fn take_pushed(&mut self, event: &Event) -> bool {
+        let position = RecordedEvent::from_sdl(event)
+            .and_then(|event| self.pushed.iter().position(|pushed| *pushed == event));
+        match position {
+            Some(position) => {
+                self.pushed.remove(position);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// This frame's events.  Call it once per frame, in place of
+    /// `event_pump.poll_iter()`.
+    pub fn poll(&mut self, event_pump: &mut EventPump) -> Vec<Event> {
+        let live: Vec<Event> = event_pump.poll_iter().collect();
+        let events = self.next_frame(live);
+        self.frame += 1;
+        events
+    }
+
+    // The events to hand out this frame, given the live ones
+    fn next_frame(&mut self, live: Vec<Event>) -> Vec<Event> {
+        let elapsed = self.start.elapsed();
+        let time_ms = elapsed.as_secs() as u32 * 1000 + elapsed.subsec_millis();
+        let frame = self.frame;
+        let pushed: Vec<bool> = live.iter().map(|event| self.take_pushed(event)).collect();
+        match self.mode {
+            Mode::Live => live,
+            Mode::Record(_, ref mut recording) => {
+                let events: Vec<RecordedEvent> = live.iter()
+                    .zip(&pushed)
+                    .filter(|&(_, &pushed)| !pushed)
+                    .filter_map(|(event, _)| RecordedEvent::from_sdl(event))
+                    .collect();
+                if !events.is_empty() {
+                    recording.frames.push(Frame { frame, time_ms, events });
+                }
+                live
+            },
+            Mode::Replay(ref mut frames) => {
+                // Still let the windows be closed, and the lesson's own
+                // events through
+                let mut events: Vec<Event> = live.into_iter()
+                    .zip(pushed)
+                    .filter(|&(ref event, pushed)| {
+                        pushed || matches!(*event, Event::Quit { .. } | Event::Window { .. })
+                    })
+                    .map(|(event, _)| event)
+                    .collect();
+                while frames.front().is_some_and(|next| next.frame <= frame) {
+                    let next = frames.pop_front().unwrap();
+                    events.extend(next.events.iter().map(|event| event.to_sdl(next.time_ms)));
+                }
+                events
+            },
+        }
+    }
+
+    /// Save the recording, when recording.  Dropping the source saves
+    /// it too, but can only print any error rather than return it.
+    pub fn finish(mut self) -> Result<()> {
+        self.save()
+    }
+
+    // Save the recording (once; it is gone afterwards)
+    fn save(&mut self) -> Result<()> {
+        match mem::replace(&mut self.mode, Mode::Live) {
+            Mode::Record(path, recording) => recording.save(&path),
+            _ => Ok(()),
+        }
+    }
+}
+
+// A lesson that bails out with `?` drops its EventSource without
+// calling `finish`, and the recording is most useful then.  `drop`
+// can't return the error, and panicking while already unwinding would
+// abort, so all that is left is to print it.
+impl Drop for EventSource {
+    fn drop(&mut self) {
+        if let Err(err) = self.save() {
+            eprintln!("{}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard;
+
+    fn key_down(keycode: Keycode) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 1, keycode: Some(keycode),
+                         scancode: Some(Scancode::A), keymod: keyboard::LSHIFTMOD,
+                         repeat: false }
+    }
+
+    fn click(x: i32, y: i32) -> Event {
+        Event::MouseButtonDown { timestamp: 0, window_id: 1, which: 0,
+                                 mouse_btn: MouseButton::Left, x, y }
+    }
+
+    #[test]
+    fn events_survive_saving() {
+        let events = vec![
+            Event::Quit { timestamp: 0 },
+            key_down(Keycode::Q),
+            click(10, 20),
+            Event::TextInput { timestamp: 0, window_id: 1, text: "é".to_string() },
+            Event::Window { timestamp: 0, window_id: 1, win_event: WindowEvent::Resized(800, 600) },
+            Event::MouseWheel { timestamp: 0, window_id: 1, which: 0, x: 0, y: -1,
+                                direction: MouseWheelDirection::Flipped },
+            Event::ControllerDeviceAdded { timestamp: 0, which: 0 },
+            Event::ControllerAxisMotion { timestamp: 0, which: 0, axis: Axis::TriggerLeft,
+                                          value: 32767 },
+            Event::ControllerButtonDown { timestamp: 0, which: 0, button: Button::DPadUp },
+            Event::JoyAxisMotion { timestamp: 0, which: 1, axis_idx: 3, value: -32768 },
+            Event::JoyButtonUp { timestamp: 0, which: 1, button_idx: 7 },
+            Event::FingerMotion { timestamp: 0, touch_id: 3, finger_id: 1, x: 0.25, y: 0.5,
+                                  dx: -0.125, dy: 0.0, pressure: 1.0 },
+            Event::MultiGesture { timestamp: 0, touch_id: 3, d_theta: 0.1, d_dist: -0.02,
+                                  x: 0.5, y: 0.5, num_fingers: 2 },
+        ];
+        let recorded: Vec<RecordedEvent> = events.iter()
+            .map(|event| RecordedEvent::from_sdl(event).unwrap())
+            .collect();
+        let recording = Recording { frames: vec![Frame { frame: 0, time_ms: 0, events: recorded }] };
+        let reloaded = Recording::parse(&recording.to_text().unwrap()).unwrap();
+        assert_eq!(reloaded, recording);
+        let replayed: Vec<Event> = reloaded.frames[0].events.iter()
+            .map(|event| event.to_sdl(0))
+            .collect();
+        assert_eq!(replayed, events);
+    }
+
+    #[test]
+    fn playback_hands_events_out_on_their_own_frames() {
+        let mut recorder = EventSource::new(Mode::Record(PathBuf::new(), Recording::default()));
+        let live = vec![vec![], vec![key_down(Keycode::A)], vec![], vec![click(1, 2), click(3, 4)]];
+        for events in &live {
+            assert_eq!(&recorder.next_frame(events.clone()), events);
+            recorder.frame += 1;
+        }
+        let recording = match mem::replace(&mut recorder.mode, Mode::Live) {
+            Mode::Record(_, recording) => recording,
+            _ => unreachable!(),
+        };
+        // Only frames with something in them are kept
+        assert_eq!(recording.frames.iter().map(|frame| frame.frame).collect::<Vec<_>>(),
+                   vec![1, 3]);
+
+        let mut player = EventSource::replay_recording(recording);
+        for events in &live {
+            // Live input is ignored while replaying
+            let replayed = player.next_frame(vec![key_down(Keycode::Z), click(5, 5)]);
+            assert_eq!(replayed.len(), events.len());
+            for (replayed, live) in replayed.iter().zip(events) {
+                assert_eq!(RecordedEvent::from_sdl(replayed), RecordedEvent::from_sdl(live));
+            }
+            player.frame += 1;
+        }
+        assert!(player.next_frame(vec![]).is_empty());
+    }
+
+    #[test]
+    fn closing_windows_and_pushed_events_get_through_a_replay() {
+        let close = Event::Window { timestamp: 0, window_id: 2, win_event: WindowEvent::Close };
+        let axis = Event::ControllerAxisMotion { timestamp: 0, which: 99, axis: Axis::LeftX,
+                                                 value: 32767 };
+        let mut player = EventSource::replay_recording(Recording::default());
+        player.expect_pushed(&axis);
+        // Pushed events come back with SDL's timestamp on them
+        let pushed = Event::ControllerAxisMotion { timestamp: 1234, which: 99,
+                                                   axis: Axis::LeftX, value: 32767 };
+        let live = vec![key_down(Keycode::A), close.clone(), pushed.clone(), axis.clone(),
+                        Event::Quit { timestamp: 0 }];
+        // The second axis event wasn't pushed, so it is real input
+        assert_eq!(player.next_frame(live),
+                   vec![close, pushed.clone(), Event::Quit { timestamp: 0 }]);
+
+        // ... and pushed events aren't recorded
+        let mut recorder = EventSource::new(Mode::Record(PathBuf::new(), Recording::default()));
+        recorder.expect_pushed(&axis);
+        assert_eq!(recorder.next_frame(vec![pushed, key_down(Keycode::A)]).len(), 2);
+        let recording = match mem::replace(&mut recorder.mode, Mode::Live) {
+            Mode::Record(_, recording) => recording,
+            _ => unreachable!(),
+        };
+        assert_eq!(recording.frames[0].events,
+                   vec![RecordedEvent::from_sdl(&key_down(Keycode::A)).unwrap()]);
+    }
+
+    #[test]
+    fn recordings_are_saved_when_dropped() {
+        let path = ::std::env::temp_dir()
+            .join(format!("lazy_foo_dropped_{}.ron", ::std::process::id()));
+        let mut recorder = EventSource::record(&path);
+        recorder.next_frame(vec![key_down(Keycode::A)]);
+        drop(recorder);
+        let recording = Recording::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(recording.frames.len(), 1);
+    }
+}
@@ -0,0 +1,119 @@
+//! Loading images and fonts from `resources/`, on disk or in memory.
+//!
+//! The lessons name their assets by paths like `"resources/foo.png"`,
+//! which only work when the binary is started from the repository
+//! root.  Built with the `embed-resources` feature, every file in
+//! `resources/` is compiled into the binary instead (see `build.rs`),
+//! and loaded from memory through an SDL `RWops`, so the lessons run
+//! from anywhere.
+//!
+//! Setting `LAZY_FOO_RESOURCES` to a directory loads from that
+//! directory on disk instead, even when resources are embedded, so
+//! assets can be edited without a rebuild.  `"resources/foo.png"` is
+//! then read from `$LAZY_FOO_RESOURCES/foo.png`.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sdl2::render::{Renderer, Texture};
+use sdl2::rwops::RWops;
+use sdl2::surface::Surface;
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use sdl2::image::ImageRWops;
+
+use error::{Error, Result};
+
+/// Directory to load resources from, in place of the embedded copies
+pub const OVERRIDE_VAR: &str = "LAZY_FOO_RESOURCES";
+
+// Defines BUNDLE, the embedded files by path; empty unless the
+// embed-resources feature is enabled
+include!(concat!(env!("OUT_DIR"), "/bundle.rs"));
+
+// Where a resource will be read from
+enum Source {
+    Disk(PathBuf),
+    Memory(&'static [u8]),
+}
+
+fn locate(path: &Path) -> Source {
+    if let Some(dir) = env::var_os(OVERRIDE_VAR) {
+        let relative = path.strip_prefix("resources").unwrap_or(path);
+        return Source::Disk(Path::new(&dir).join(relative));
+    }
+    match embedded(path) {
+        Some(bytes) => Source::Memory(bytes),
+        None => Source::Disk(path.to_path_buf()),
+    }
+}
+
+/// The embedded copy of `path`, if it was compiled in.
+pub fn embedded(path: &Path) -> Option<&'static [u8]> {
+    BUNDLE.iter()
+        .find(|&&(name, _)| Path::new(name) == path)
+        .map(|&(_, bytes)| bytes)
+}
+
+/// True if `path` can be loaded, from either source.
+pub fn exists(path: &Path) -> bool {
+    match locate(path) {
+        Source::Disk(path) => path.exists(),
+        Source::Memory(_) => true,
+    }
+}
+
+/// Open `path` for reading, from memory if it is embedded and from
+/// disk otherwise.
+pub fn open(path: &Path) -> Result<RWops<'static>> {
+    match locate(path) {
+        Source::Disk(file) => {
+            Error::check_asset(&file)?;
+            RWops::from_file(&file, "rb").map_err(|err| Error::image(&file, err))
+        },
+        Source::Memory(bytes) => {
+            RWops::from_bytes(bytes).map_err(|err| Error::image(path, err))
+        },
+    }
+}
+
+/// Read the whole of `path`, for data files that we parse ourselves.
+pub fn read(path: &Path) -> Result<Vec<u8>> {
+    match locate(path) {
+        Source::Disk(file) => {
+            Error::check_asset(&file)?;
+            fs::read(&file).map_err(|err| Error::data(&file, err))
+        },
+        Source::Memory(bytes) => Ok(bytes.to_vec()),
+    }
+}
+
+/// As `read`, for text files.
+pub fn read_to_string(path: &Path) -> Result<String> {
+    String::from_utf8(read(path)?).map_err(|err| Error::data(path, err))
+}
+
+/// Load an image in any format SDL_image supports.
+pub fn load_surface(path: &Path) -> Result<Surface<'static>> {
+    let rwops = open(path)?;
+    let surface = rwops.load().map_err(|err| Error::image(path, err))?;
+    // The surface SDL_image hands back is tied to the lifetime of the
+    // RWops it was read from, although SDL doesn't need the RWops once
+    // loading is done.  Copying it gives us one we can return.
+    surface.convert(&surface.pixel_format())
+        .map_err(|err| Error::image(path, err))
+}
+
+/// Load an image straight into a texture.
+pub fn load_texture(renderer: &Renderer, path: &Path) -> Result<Texture> {
+    let surface = load_surface(path)?;
+    renderer.create_texture_from_surface(&surface).map_err(Error::texture)
+}
+
+/// Load a TrueType font at `size` points.
+pub fn load_font<'ttf>(ttf: &'ttf Sdl2TtfContext, path: &Path, size: u16) -> Result<Font<'ttf>> {
+    let rwops = open(path)?;
+    ttf.load_font_from_rwops(rwops, size)
+        .map_err(|err| Error::font(path, err))
+}
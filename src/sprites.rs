@@ -0,0 +1,222 @@
+//! Sprite sheets whose clip rectangles come from a data file.
+//!
+//! Next to each sprite sheet image is a [RON](https://github.com/ron-rs/ron)
+//! file with the same name, which names the clips in it.  A clip is
+//! either a single rectangle, or a grid of equally sized frames, which
+//! saves writing out every frame of an animation by hand:
+//!
+//! ```text
+//! (
+//!     clips: {
+//!         "red": Rect(x: 0, y: 0, w: 100, h: 100),
+//!         // Four 64x205 frames side by side, starting at (0, 0)
+//!         "walk": Grid(w: 64, h: 205, columns: 4),
+//!     },
+//! )
+//! ```
+//!
+//! `Grid` also takes `x` and `y` (where the first frame is, default 0)
+//! and `rows` (default 1); frames are numbered left to right, then top
+//! to bottom.  Every frame is checked against the size of the image
+//! when the sheet is loaded, so a mistyped rectangle is reported
+//! straight away rather than drawing garbage.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use ron;
+use sdl2::rect::Rect;
+use sdl2::render::Renderer;
+
+use error::{Error, Result};
+use resources;
+use texture::LTexture;
+
+/// How a clip is laid out on the sheet.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum ClipSpec {
+    /// A single frame
+    Rect { x: i32, y: i32, w: u32, h: u32 },
+    /// `columns` x `rows` frames of `w` x `h`, the first at (`x`, `y`)
+    Grid {
+        #[serde(default)]
+        x: i32,
+        #[serde(default)]
+        y: i32,
+        w: u32,
+        h: u32,
+        columns: u32,
+        #[serde(default = "one_row")]
+        rows: u32,
+    },
+}
+
+fn one_row() -> u32 {
+    1
+}
+
+/// The contents of a sprite sheet's data file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Descriptor {
+    pub clips: BTreeMap<String, ClipSpec>,
+}
+
+impl Descriptor {
+
+    pub fn parse(text: &str) -> ::std::result::Result<Descriptor, String> {
+        ron::from_str(text).map_err(|err| err.to_string())
+    }
+
+    /// Work out every clip's frames, checking that they all lie inside
+    /// a `width` x `height` image.
+    pub fn frames(&self, width: u32, height: u32)
+                  -> ::std::result::Result<HashMap<String, Vec<Rect>>, String> {
+        let mut clips = HashMap::new();
+        for (name, spec) in &self.clips {
+            let frames = match *spec {
+                ClipSpec::Rect { x, y, w, h } => vec![(x, y, w, h)],
+                ClipSpec::Grid { x, y, w, h, columns, rows } => {
+                    if columns == 0 || rows == 0 {
+                        return Err(format!("clip '{}' is a grid with no frames", name));
+                    }
+                    // Check the whole grid fits before working out its
+                    // frames, so huge sizes or counts can't overflow
+                    let (across, down) = (u64::from(w) * u64::from(columns),
+                                          u64::from(h) * u64::from(rows));
+                    if !fits(x, y, across, down, width, height) {
+                        return Err(format!("clip '{}' ({}x{} frames of {}x{} at {}, {}) \
+                                            is not inside the {}x{} image",
+                                           name, columns, rows, w, h, x, y, width, height));
+                    }
+                    (0..rows)
+                        .flat_map(|row| (0..columns).map(move |column| {
+                            (x + (column * w) as i32, y + (row * h) as i32, w, h)
+                        }))
+                        .collect()
+                },
+            };
+            for (i, &(x, y, w, h)) in frames.iter().enumerate() {
+                if !fits(x, y, u64::from(w), u64::from(h), width, height) {
+                    return Err(format!("frame {} of clip '{}' ({}x{} at {}, {}) \
+                                        is not inside the {}x{} image",
+                                       i, name, w, h, x, y, width, height));
+                }
+            }
+            let rects = frames.into_iter().map(|(x, y, w, h)| Rect::new(x, y, w, h)).collect();
+            clips.insert(name.clone(), rects);
+        }
+        Ok(clips)
+    }
+}
+
+// Whether a `w` x `h` rectangle at (`x`, `y`) is inside a `width` x
+// `height` image, worked out in u64 so that nothing can overflow
+fn fits(x: i32, y: i32, w: u64, h: u64, width: u32, height: u32) -> bool {
+    w > 0 && h > 0 && x >= 0 && y >= 0 &&
+        x as u64 + w <= u64::from(width) && y as u64 + h <= u64::from(height)
+}
+
+/// The data file that describes the sprite sheet at `image`.
+pub fn descriptor_path(image: &Path) -> PathBuf {
+    image.with_extension("ron")
+}
+
+/// A texture, and the named clips on it.
+pub struct SpriteSheet {
+    pub texture: LTexture,
+    clips: HashMap<String, Vec<Rect>>,
+}
+
+impl SpriteSheet {
+
+    /// Load the image at `path` (color keyed, like any other
+    /// `LTexture`) along with the clips described next to it.
+    pub fn load(renderer: &Renderer, path: &Path) -> Result<SpriteSheet> {
+        let texture = LTexture::new_from_file(renderer, path)?;
+        let data_path = descriptor_path(path);
+        let text = resources::read_to_string(&data_path)?;
+        let clips = Descriptor::parse(&text)
+            .and_then(|descriptor| descriptor.frames(texture.width, texture.height))
+            .map_err(|err| Error::data(&data_path, err))?;
+        Ok(SpriteSheet { texture, clips })
+    }
+
+    /// Every frame of the clip called `name`, in order.
+    pub fn frames(&self, name: &str) -> Result<&[Rect]> {
+        self.clips.get(name)
+            .map(|frames| frames.as_slice())
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))
+    }
+
+    /// The clip called `name`; the first frame, if it has more than one.
+    pub fn clip(&self, name: &str) -> Result<Rect> {
+        self.frames(name).map(|frames| frames[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(text: &str, width: u32, height: u32)
+              -> ::std::result::Result<HashMap<String, Vec<Rect>>, String> {
+        Descriptor::parse(text)?.frames(width, height)
+    }
+
+    #[test]
+    fn single_rects() {
+        let clips = frames(r#"(clips: { "a": Rect(x: 100, y: 0, w: 100, h: 50) })"#,
+                           200, 200).unwrap();
+        assert_eq!(clips["a"], vec![Rect::new(100, 0, 100, 50)]);
+    }
+
+    #[test]
+    fn grids_are_sliced_row_by_row() {
+        let clips = frames(r#"(clips: { "g": Grid(x: 10, w: 20, h: 30, columns: 2, rows: 2) })"#,
+                           100, 100).unwrap();
+        assert_eq!(clips["g"], vec![Rect::new(10, 0, 20, 30), Rect::new(30, 0, 20, 30),
+                                    Rect::new(10, 30, 20, 30), Rect::new(30, 30, 20, 30)]);
+    }
+
+    #[test]
+    fn frames_outside_the_image_are_rejected() {
+        // The old lesson 14 typo: a fourth 64 pixel frame at x=196
+        let text = r#"(clips: { "walk": Rect(x: 196, y: 0, w: 64, h: 205) })"#;
+        let err = frames(text, 256, 205).unwrap_err();
+        assert!(err.contains("'walk'"), "{}", err);
+        assert!(frames(r#"(clips: { "g": Grid(w: 64, h: 205, columns: 5) })"#, 256, 205).is_err());
+        assert!(frames(r#"(clips: { "g": Grid(w: 64, h: 205, columns: 0) })"#, 256, 205).is_err());
+        assert!(frames(r#"(clips: { "r": Rect(x: -1, y: 0, w: 4, h: 4) })"#, 256, 205).is_err());
+    }
+
+    #[test]
+    fn huge_clips_are_rejected_without_overflowing() {
+        let text = r#"(clips: { "r": Rect(x: 2147483647, y: 0, w: 4294967295, h: 1) })"#;
+        assert!(frames(text, 256, 205).is_err());
+        let text = r#"(clips: { "g": Grid(x: 64, w: 4294967295, h: 4294967295,
+                                         columns: 4294967295, rows: 4294967295) })"#;
+        let err = frames(text, 256, 205).unwrap_err();
+        assert!(err.contains("'g'"), "{}", err);
+    }
+
+    #[test]
+    fn syntax_errors_are_reported() {
+        assert!(Descriptor::parse("(clips: { \"a\": Square() })").is_err());
+    }
+
+    // The descriptors the lessons use, against the size of their images
+    #[test]
+    fn shipped_descriptors_fit_their_images() {
+        let sheets = [("resources/dots.png", 200, 200),
+                      ("resources/foo2.png", 256, 205),
+                      ("resources/button.png", 300, 800)];
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for &(image, width, height) in &sheets {
+            let path = root.join(descriptor_path(Path::new(image)));
+            let text = ::std::fs::read_to_string(&path).unwrap();
+            if let Err(err) = frames(&text, width, height) {
+                panic!("{}: {}", path.display(), err);
+            }
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! A line of text being typed in.
+//!
+//! SDL doesn't hand out text a key at a time.  Between
+//! `text_input().start()` and `stop()`, typed text arrives in
+//! `TextInput` events, as UTF-8 strings that may be more than one
+//! character long - a dead key and a letter, or a whole word from an
+//! input method (IME).  While an IME is putting a word together it
+//! sends `TextEditing` events with the unfinished *composition*, which
+//! should be shown at the cursor but isn't part of the text until the
+//! `TextInput` that commits it.
+//!
+//! Keys that edit rather than type (Backspace, Delete, the arrows, Home
+//! and End) still come as `KeyDown`s.  `TextBox` handles both, keeps
+//! the cursor on a character boundary, and asks for copy and paste on
+//! Ctrl+C and Ctrl+V, so that the caller can use SDL's clipboard:
+//!
+//! ```text
+//! match text.handle_event(&event) {
+//!     Some(TextAction::Copy) => clipboard.set_clipboard_text(text.text())?,
+//!     Some(TextAction::Paste) => text.insert(&clipboard.clipboard_text()?),
+//!     ...
+//! }
+//! ```
+//!
+//! The cursor moves a `char` at a time, so a letter written with a
+//! combining accent takes two presses of an arrow key to get past.
+
+use std::ops::Range;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use input::Modifiers;
+
+/// What `TextBox::handle_event` did, or wants done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAction {
+    /// The text, the cursor or the composition changed, so it needs
+    /// drawing again
+    Changed,
+    /// Ctrl+C: put the text on the clipboard
+    Copy,
+    /// Ctrl+V: `insert` what is on the clipboard
+    Paste,
+}
+
+/// Text, a cursor in it, and whatever an input method is composing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextBox {
+    text: String,
+    // Byte offset, always on a char boundary
+    cursor: usize,
+    composition: String,
+    // Where the IME's own cursor is in the composition, in chars
+    composition_cursor: usize,
+    // The modifiers held as of the last key event; some platforms send
+    // the 'c' of Ctrl+C as text as well
+    modifiers: Modifiers,
+}
+
+impl TextBox {
+
+    pub fn new() -> TextBox {
+        TextBox::default()
+    }
+
+    /// A text box holding `text`, with the cursor at the end.
+    pub fn with_text(text: &str) -> TextBox {
+        TextBox { text: text.to_string(), cursor: text.len(), ..TextBox::default() }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The cursor, as a byte offset into `text()`.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The text an input method is still working on, if any.
+    pub fn composition(&self) -> &str {
+        &self.composition
+    }
+
+    /// Replace the text, leaving the cursor at the end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = text.len();
+    }
+
+    /// Type `text` at the cursor, and move the cursor past it.
+    pub fn insert(&mut self, text: &str) {
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Delete the character before the cursor.  False if there isn't one.
+    pub fn backspace(&mut self) -> bool {
+        match self.text[..self.cursor].chars().next_back() {
+            Some(c) => {
+                self.cursor -= c.len_utf8();
+                self.text.remove(self.cursor);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Delete the character after the cursor.  False if there isn't one.
+    pub fn delete(&mut self) -> bool {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Move the cursor back a character.  False if it is at the start.
+    pub fn move_left(&mut self) -> bool {
+        match self.text[..self.cursor].chars().next_back() {
+            Some(c) => {
+                self.cursor -= c.len_utf8();
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Move the cursor on a character.  False if it is at the end.
+    pub fn move_right(&mut self) -> bool {
+        match self.text[self.cursor..].chars().next() {
+            Some(c) => {
+                self.cursor += c.len_utf8();
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn move_home(&mut self) -> bool {
+        let moved = self.cursor != 0;
+        self.cursor = 0;
+        moved
+    }
+
+    pub fn move_end(&mut self) -> bool {
+        let moved = self.cursor != self.text.len();
+        self.cursor = self.text.len();
+        moved
+    }
+
+    /// What to draw: the text with any composition at the cursor.
+    pub fn display_text(&self) -> String {
+        let mut display = self.text.clone();
+        display.insert_str(self.cursor, &self.composition);
+        display
+    }
+
+    /// Where to draw the cursor, as a byte offset into `display_text()`.
+    /// While composing, that is the IME's cursor in the composition.
+    pub fn display_cursor(&self) -> usize {
+        let in_composition: usize = self.composition.chars()
+            .take(self.composition_cursor)
+            .map(char::len_utf8)
+            .sum();
+        self.cursor + in_composition
+    }
+
+    /// The composition's bytes in `display_text()`, to underline it.
+    pub fn composition_range(&self) -> Option<Range<usize>> {
+        if self.composition.is_empty() {
+            None
+        } else {
+            Some(self.cursor..self.cursor + self.composition.len())
+        }
+    }
+
+    /// Edit the text from a text, editing or key event.  Other events
+    /// are ignored.
+    pub fn handle_event(&mut self, event: &Event) -> Option<TextAction> {
+        match *event {
+            Event::TextInput { ref text, .. } => {
+                // Ctrl+C and Ctrl+V aren't typing
+                let shortcut = self.modifiers.ctrl &&
+                    (text.eq_ignore_ascii_case("c") || text.eq_ignore_ascii_case("v"));
+                let had_composition = !self.composition.is_empty();
+                self.composition.clear();
+                self.composition_cursor = 0;
+                if shortcut {
+                    return if had_composition { Some(TextAction::Changed) } else { None };
+                }
+                self.insert(text);
+                Some(TextAction::Changed)
+            },
+            Event::TextEditing { ref text, start, .. } => {
+                self.composition = text.clone();
+                self.composition_cursor = start.max(0) as usize;
+                Some(TextAction::Changed)
+            },
+            Event::KeyDown { keycode: Some(keycode), keymod, .. } => {
+                self.modifiers = Modifiers::from_mod(keymod);
+                // The input method has the keys while it is composing
+                if !self.composition.is_empty() {
+                    return None;
+                }
+                let changed = match keycode {
+                    Keycode::C if self.modifiers.ctrl => return Some(TextAction::Copy),
+                    Keycode::V if self.modifiers.ctrl => return Some(TextAction::Paste),
+                    Keycode::Backspace => self.backspace(),
+                    Keycode::Delete => self.delete(),
+                    Keycode::Left => self.move_left(),
+                    Keycode::Right => self.move_right(),
+                    Keycode::Home => self.move_home(),
+                    Keycode::End => self.move_end(),
+                    _ => false,
+                };
+                if changed { Some(TextAction::Changed) } else { None }
+            },
+            Event::KeyUp { keymod, .. } => {
+                self.modifiers = Modifiers::from_mod(keymod);
+                None
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::{self, Mod};
+
+    fn key(keycode: Keycode, keymod: Mod) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None,
+                         keymod, repeat: false }
+    }
+
+    fn typed(text: &str) -> Event {
+        Event::TextInput { timestamp: 0, window_id: 0, text: text.to_string() }
+    }
+
+    #[test]
+    fn editing_keeps_to_character_boundaries() {
+        let mut text = TextBox::with_text("naïve");
+        assert_eq!(text.handle_event(&key(Keycode::Left, keyboard::NOMOD)),
+                   Some(TextAction::Changed));
+        text.move_left();
+        text.move_left();
+        assert_eq!(text.cursor(), 2);
+        text.move_right();
+        assert_eq!(text.cursor(), 4);
+        assert!(text.backspace());
+        assert_eq!(text.text(), "nave");
+        text.handle_event(&typed("ï"));
+        text.handle_event(&key(Keycode::Delete, keyboard::NOMOD));
+        assert_eq!(text.text(), "naïe");
+
+        text.handle_event(&key(Keycode::Home, keyboard::NOMOD));
+        assert!(!text.backspace());
+        assert_eq!(text.handle_event(&key(Keycode::Left, keyboard::NOMOD)), None);
+        text.handle_event(&key(Keycode::End, keyboard::NOMOD));
+        text.handle_event(&typed("🦀"));
+        assert!(text.backspace());
+        assert_eq!(text.text(), "naïe");
+    }
+
+    #[test]
+    fn compositions_show_at_the_cursor_until_committed() {
+        let mut text = TextBox::with_text("ab");
+        text.move_left();
+        text.handle_event(&Event::TextEditing { timestamp: 0, window_id: 0,
+                                                text: "かな".to_string(), start: 1, length: 0 });
+        assert_eq!(text.text(), "ab");
+        assert_eq!(text.display_text(), "aかなb");
+        assert_eq!(text.display_cursor(), 1 + "か".len());
+        assert_eq!(text.composition_range(), Some(1..1 + "かな".len()));
+        // Editing keys belong to the input method meanwhile
+        assert_eq!(text.handle_event(&key(Keycode::Backspace, keyboard::NOMOD)), None);
+
+        text.handle_event(&typed("仮名"));
+        assert_eq!(text.text(), "a仮名b");
+        assert_eq!(text.composition_range(), None);
+        assert_eq!(text.display_cursor(), text.cursor());
+    }
+
+    #[test]
+    fn ctrl_c_and_ctrl_v_ask_for_the_clipboard() {
+        let mut text = TextBox::with_text("Some Text");
+        assert_eq!(text.handle_event(&key(Keycode::C, keyboard::LCTRLMOD)),
+                   Some(TextAction::Copy));
+        // ... and the 'c' some platforms send as text is dropped
+        assert_eq!(text.handle_event(&typed("c")), None);
+        assert_eq!(text.handle_event(&key(Keycode::V, keyboard::RCTRLMOD)),
+                   Some(TextAction::Paste));
+        text.insert(" pasted");
+        assert_eq!(text.text(), "Some Text pasted");
+        // Without Ctrl, C is just a letter
+        assert_eq!(text.handle_event(&key(Keycode::C, keyboard::NOMOD)), None);
+        assert_eq!(text.handle_event(&typed("c")), Some(TextAction::Changed));
+        assert_eq!(text.text(), "Some Text pastedc");
+    }
+}
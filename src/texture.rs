@@ -0,0 +1,493 @@
+use std::mem;
+use std::path::Path;
+
+use sdl2::render::{BlendMode, Renderer, Texture};
+use sdl2::surface::Surface;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Rect, Point};
+use sdl2::ttf::Font;
+
+use error::{Error, Result};
+use resources;
+
+// Streaming textures and pixel buffers hold their pixels as R, G, B, A
+// bytes in that order.  The formats are named for a 32-bit value, not
+// the bytes in memory, so which one that is depends on the machine's
+// byte order (SDL's RGBA32 alias, which does this for us, is newer
+// than this version of rust-sdl2).
+#[cfg(target_endian = "little")]
+pub const PIXEL_FORMAT: PixelFormatEnum = PixelFormatEnum::ABGR8888;
+#[cfg(target_endian = "big")]
+pub const PIXEL_FORMAT: PixelFormatEnum = PixelFormatEnum::RGBA8888;
+
+// Render targets are whatever format SDL likes best for drawing into
+const TARGET_FORMAT: PixelFormatEnum = PixelFormatEnum::RGBA8888;
+
+// One pixel: red, green, blue and alpha
+pub type Rgba = [u8; 4];
+
+// Create a struct that will track texture data
+pub struct LTexture {
+    // The actual texture.
+    pub texture: Texture,
+    // Image dimensions
+    pub width: u32,
+    pub height: u32,
+    // Render targets only: what `texture` is swapped for while the
+    // renderer is drawing into it
+    stand_in: Option<Texture>,
+}
+
+// Implement a few functions for the Texture struct
+// Note that Rust doesn't put much focus on data hiding
+// or other OOP concepts so we don't care about having
+// getters and setters or the like.
+//
+// Instead, since Rust treats values as immutable by
+// default, we don't have to worry about accidentally
+// setting a struct field unless we create an LTexture
+// using 'mut', in which case we take on the responsibility
+// of ensuring the fields don't get messed with.
+//
+// This 'hands off' by default approach helps eliminate
+// a lot of problems that, in OOP, are handled by boilerplate code.
+// The result is cleaner, more consise and ultimately more safe.
+impl LTexture {
+
+    // create a new texture
+    pub fn new(tex: Texture) -> LTexture {
+        let w = tex.query().width;
+        let h = tex.query().height;
+        LTexture {
+            texture: tex,
+            width: w,
+            height: h,
+            stand_in: None,
+        }
+    }
+
+    // Load a texture from a file
+    pub fn new_from_file(renderer: &Renderer, path: &Path) -> Result<LTexture> {
+        // Load the surface first, so we can set the color key
+        let mut surface = resources::load_surface(path)?;
+
+        // Now set the color key on the surface
+        surface.set_color_key(true, Color::RGB(0, 0xff, 0xff))
+            .map_err(|err| Error::image(path, err))?;
+
+        // Convert the surface to a texture and pass it to
+        // LTexture::new to be wrapped
+        let tex = renderer.create_texture_from_surface(&surface)
+            .map_err(Error::texture)?;
+        Ok(LTexture::new(tex))
+    }
+
+    // Upload an image from memory, with its alpha channel blended
+    pub fn from_pixels(renderer: &Renderer, image: &PixelBuffer) -> Result<LTexture> {
+        let mut tex = renderer.create_texture_static(PIXEL_FORMAT, image.width, image.height)
+            .map_err(Error::texture)?;
+        tex.update(None, image.pixels.as_flattened(), image.width as usize * 4)
+            .map_err(Error::texture)?;
+        tex.set_blend_mode(BlendMode::Blend);
+        Ok(LTexture::new(tex))
+    }
+
+    // Render the given text with a font, and wrap the result
+    pub fn load_from_rendered_text(renderer: &Renderer,
+                                   font: &Font,
+                                   text: &str,
+                                   color: Color) -> Result<LTexture> {
+        let text_surface: Surface = font.render(text)
+            .solid(color)
+            .map_err(Error::text)?;
+        // Now create a texture from the surface using the supplied renderer
+        let text_texture = renderer.create_texture_from_surface(&text_surface)
+            .map_err(Error::texture)?;
+        // Return an LTexture using the given text_texture
+        Ok(LTexture::new(text_texture))
+    }
+
+    // Create a blank texture that can be drawn into, rather than
+    // loaded: a render target.  Draw into it with `draw_into`, then
+    // render it like any other texture.
+    pub fn new_target(renderer: &Renderer, width: u32, height: u32) -> Result<LTexture> {
+        if !renderer.render_target_supported() {
+            return Err(Error::texture("this renderer can't draw into textures"));
+        }
+        let tex = renderer.create_texture_target(TARGET_FORMAT, width, height)
+            .map_err(Error::texture)?;
+        // rust-sdl2 takes the texture while it is the target, and gives
+        // it back when the target is reset, so make something to leave
+        // in its place meanwhile
+        let stand_in = renderer.create_texture_target(TARGET_FORMAT, 1, 1)
+            .map_err(Error::texture)?;
+        Ok(LTexture { stand_in: Some(stand_in), ..LTexture::new(tex) })
+    }
+
+    // Point the renderer at this texture, run `draw` - which can use
+    // all the usual drawing calls, in the texture's own coordinates -
+    // and point it back at the window.  Only works on textures made
+    // with `new_target` (anything else is an error, and left alone),
+    // and they can't be nested: drawing into one texture while drawing
+    // into another leaves the renderer drawing to the window afterwards.
+    // If SDL won't switch targets, the texture is left blank rather
+    // than broken.
+    pub fn draw_into<F>(&mut self, renderer: &mut Renderer, draw: F) -> Result<()>
+        where F: FnOnce(&mut Renderer)
+    {
+        let mut target = renderer.render_target()
+            .ok_or_else(|| Error::texture("this renderer can't draw into textures"))?;
+        let stand_in = self.stand_in.take()
+            .ok_or_else(|| Error::texture("only textures made with new_target can be drawn into"))?;
+        let tex = mem::replace(&mut self.texture, stand_in);
+        if let Err(err) = target.set(tex) {
+            // rust-sdl2 has already thrown the texture away
+            self.replace_lost_texture(renderer)?;
+            return Err(Error::texture(err));
+        }
+
+        draw(renderer);
+
+        let reset = renderer.render_target()
+            .ok_or_else(|| Error::texture("this renderer can't draw into textures"))
+            .and_then(|mut target| target.reset().map_err(Error::texture))
+            .and_then(|tex| tex.ok_or_else(|| Error::texture("the render target went missing")));
+        match reset {
+            Ok(tex) => {
+                self.stand_in = Some(mem::replace(&mut self.texture, tex));
+                Ok(())
+            },
+            Err(err) => {
+                // The texture is still the renderer's target, out of reach
+                self.replace_lost_texture(renderer)?;
+                Err(err)
+            },
+        }
+    }
+
+    // Swap a blank texture the same size in for one drawn into and
+    // lost, so that the stand-in is back and this can be drawn into
+    // again
+    fn replace_lost_texture(&mut self, renderer: &Renderer) -> Result<()> {
+        let blank = renderer.create_texture_target(TARGET_FORMAT, self.width, self.height)
+            .map_err(Error::texture)?;
+        self.stand_in = Some(mem::replace(&mut self.texture, blank));
+        Ok(())
+    }
+
+    // Renders a texture (or the clipped part of it) with its top-left
+    // corner at the given position.  This is the plain blit used by
+    // most of the lessons, see render_to for rotation and flipping.
+    pub fn render(&self,
+                  renderer: &mut Renderer,
+                  x: i32,
+                  y: i32,
+                  clip: Option<Rect>) -> Result<()> {
+        self.render_to(renderer, x, y, clip, None, None, false, false)
+    }
+
+    // Renders a texture to a given point using a provided renderer
+    // provide additional arguments for rotation and flipping
+    // Rust doesn't provide default arguments, and it seems overkill
+    // to provide additional function signatures for this, so we're
+    // going to wrap rotation and flipping args in Option<> so we can
+    // provide None when we don't care about it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_to(&self,
+                     renderer: &mut Renderer,
+                     x: i32,
+                     y: i32,
+                     clip: Option<Rect>,
+                     rotation: Option<f64>,
+                     center: Option<Point>,
+                     flip_h: bool,
+                     flip_v: bool) -> Result<()> {
+        let clip_rect = match clip {
+            Some(rect) => rect,
+            None       => Rect::new(0, 0, self.width, self.height)
+        };
+        let rot: f64 = rotation.unwrap_or(0.0);
+
+        renderer.copy_ex(&self.texture,
+                         Some(clip_rect),
+                         Some(Rect::new(x, y,
+                                        clip_rect.width(),
+                                        clip_rect.height())),
+                         rot,
+                         center,
+                         flip_h,
+                         flip_v)
+            .map_err(Error::render)
+    }
+
+    // Modulate the LTexture using a Color - this will 'tint' the texture
+    pub fn set_color(&mut self, color: Color) {
+        let (r, g, b) = color.rgb();
+        self.texture.set_color_mod(r, g, b);
+    }
+
+    // Set the alpha channel of the texture, controlling its transparency
+    pub fn set_alpha(&mut self, alpha: u8) {
+        self.texture.set_alpha_mod(alpha);
+    }
+}
+
+// An image in memory, a row at a time from the top, that the CPU can
+// read and write - a frame from an emulator or a video, say - and a
+// StreamingTexture can show.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PixelBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Rgba>,
+}
+
+impl PixelBuffer {
+
+    // A transparent black image, or an error if it would have more
+    // pixels than memory can be asked for
+    pub fn new(width: u32, height: u32) -> Result<PixelBuffer> {
+        let len = (width as usize).checked_mul(height as usize)
+            .filter(|&len| len <= isize::MAX as usize / mem::size_of::<Rgba>())
+            .ok_or_else(|| Error::texture(format!("a {}x{} image is too big", width, height)))?;
+        Ok(PixelBuffer { width, height, pixels: vec![[0; 4]; len] })
+    }
+
+    // Where pixel (x, y) is in `pixels`
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    // Load an image file, whatever format it is in
+    pub fn load(path: &Path) -> Result<PixelBuffer> {
+        let mut image = resources::load_surface(path)?;
+        PixelBuffer::from_surface(&mut image)
+    }
+
+    // Copy a surface's pixels, converting them from whatever format
+    // the surface is in.  Any color key or blend mode on the surface is
+    // turned off, so its pixels come through untouched.
+    pub fn from_surface(image: &mut Surface) -> Result<PixelBuffer> {
+        let (width, height) = image.size();
+
+        // Blit the image into a surface that is laid out the way we
+        // want, and copy its rows out (rows can have padding at the end)
+        let mut converted = Surface::new(width, height, PIXEL_FORMAT)
+            .map_err(Error::texture)?;
+        image.set_blend_mode(BlendMode::None).map_err(Error::texture)?;
+        image.set_color_key(false, Color::RGB(0, 0, 0)).map_err(Error::texture)?;
+        image.blit(None, &mut converted, None).map_err(Error::texture)?;
+        let pitch = converted.pitch() as usize;
+        let pixels = converted.with_lock(|bytes| {
+            bytes.chunks(pitch)
+                .take(height as usize)
+                .flat_map(|row| row[..width as usize * 4].as_chunks::<4>().0.to_vec())
+                .collect()
+        });
+        Ok(PixelBuffer { width, height, pixels })
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Rgba {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: Rgba) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    // Change every pixel that is exactly `from` to `to`, returning how
+    // many there were.  Replacing a color with transparent black is
+    // what a color key does.
+    pub fn replace(&mut self, from: Rgba, to: Rgba) -> usize {
+        let mut replaced = 0;
+        for pixel in self.pixels.iter_mut().filter(|pixel| **pixel == from) {
+            *pixel = to;
+            replaced += 1;
+        }
+        replaced
+    }
+
+    // Run every pixel through `change`
+    pub fn map<F: FnMut(Rgba) -> Rgba>(&mut self, mut change: F) {
+        for pixel in &mut self.pixels {
+            *pixel = change(*pixel);
+        }
+    }
+
+    // The pixels in row `y`
+    pub fn row(&self, y: u32) -> &[Rgba] {
+        let start = self.index(0, y);
+        &self.pixels[start..start + self.width as usize]
+    }
+
+    // A copy of the part of the image inside `rect`, or None if any of
+    // `rect` is off the edge of the image
+    pub fn crop(&self, rect: Rect) -> Option<PixelBuffer> {
+        if rect.x() < 0 || rect.y() < 0
+            || rect.x() as u64 + u64::from(rect.width()) > u64::from(self.width)
+            || rect.y() as u64 + u64::from(rect.height()) > u64::from(self.height) {
+            return None;
+        }
+        let (x, width) = (rect.x() as usize, rect.width() as usize);
+        let pixels = (rect.y() as u32..rect.y() as u32 + rect.height())
+            .flat_map(|y| self.row(y)[x..x + width].iter().cloned())
+            .collect();
+        Some(PixelBuffer { width: rect.width(), height: rect.height(), pixels })
+    }
+}
+
+// The pixels of a locked StreamingTexture.  Rows can be longer than
+// the texture is wide, so the pixels are handed out a row at a time.
+pub struct Pixels<'a> {
+    bytes: &'a mut [u8],
+    pitch: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Pixels<'a> {
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // The pixels in row `y`, to be written
+    pub fn row_mut(&mut self, y: usize) -> &mut [Rgba] {
+        let start = y * self.pitch;
+        self.bytes[start..start + self.width * 4].as_chunks_mut::<4>().0
+    }
+
+    // Set every pixel to `color`
+    pub fn fill(&mut self, color: Rgba) {
+        for y in 0..self.height {
+            for pixel in self.row_mut(y) {
+                *pixel = color;
+            }
+        }
+    }
+
+    // Copy in a whole image, which has to be the same size as the
+    // texture
+    pub fn copy_from(&mut self, image: &PixelBuffer) -> Result<()> {
+        if image.width as usize != self.width || image.height as usize != self.height {
+            return Err(Error::texture(format!("a {}x{} image doesn't fit a {}x{} texture",
+                                              image.width, image.height,
+                                              self.width, self.height)));
+        }
+        for y in 0..self.height {
+            self.row_mut(y).copy_from_slice(image.row(y as u32));
+        }
+        Ok(())
+    }
+}
+
+// A texture whose pixels are written by the CPU, every frame if need
+// be, rather than loaded once from a surface
+pub struct StreamingTexture {
+    pub texture: Texture,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl StreamingTexture {
+
+    pub fn new(renderer: &Renderer, width: u32, height: u32) -> Result<StreamingTexture> {
+        let mut texture = renderer.create_texture_streaming(PIXEL_FORMAT, width, height)
+            .map_err(Error::texture)?;
+        // Let the alpha channel through
+        texture.set_blend_mode(BlendMode::Blend);
+        Ok(StreamingTexture { texture, width, height })
+    }
+
+    // Lock the texture and hand its pixels to `write`, then unlock it
+    // so it can be rendered.  What is in the pixels beforehand is
+    // undefined: `write` should set every one of them.
+    pub fn lock<F, R>(&mut self, write: F) -> Result<R>
+        where F: FnOnce(&mut Pixels) -> R
+    {
+        let (width, height) = (self.width as usize, self.height as usize);
+        self.texture.with_lock(None, |bytes, pitch| {
+            write(&mut Pixels { bytes, pitch, width, height })
+        }).map_err(Error::texture)
+    }
+
+    // Replace the texture's pixels with `image`'s
+    pub fn update(&mut self, image: &PixelBuffer) -> Result<()> {
+        self.lock(|pixels| pixels.copy_from(image))?
+    }
+
+    // Render the whole texture with its top-left corner at (x, y)
+    pub fn render(&self, renderer: &mut Renderer, x: i32, y: i32) -> Result<()> {
+        renderer.copy(&self.texture, None, Some(Rect::new(x, y, self.width, self.height)))
+            .map_err(Error::render)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locked_pixels_skip_row_padding() {
+        // A 2x2 texture whose rows are 3 pixels long
+        let mut bytes = vec![0xee; 24];
+        let mut image = PixelBuffer::new(2, 2).unwrap();
+        image.pixels = vec![[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 15, 16]];
+        {
+            let mut pixels = Pixels { bytes: &mut bytes, pitch: 12, width: 2, height: 2 };
+            pixels.copy_from(&image).unwrap();
+        }
+        assert_eq!(&bytes[..12], &[1, 2, 3, 4, 5, 6, 7, 8, 0xee, 0xee, 0xee, 0xee]);
+        assert_eq!(&bytes[12..20], &[9, 10, 11, 12, 13, 14, 15, 16]);
+        assert_eq!(&bytes[20..], &[0xee; 4]);
+    }
+
+    #[test]
+    fn images_must_fit_the_texture() {
+        let mut bytes = vec![0xee; 16];
+        let mut pixels = Pixels { bytes: &mut bytes, pitch: 8, width: 2, height: 2 };
+        assert!(pixels.copy_from(&PixelBuffer::new(2, 3).unwrap()).is_err());
+        assert!(pixels.copy_from(&PixelBuffer::new(1, 2).unwrap()).is_err());
+        assert_eq!(bytes, vec![0xee; 16]);
+    }
+
+    #[test]
+    fn huge_images_are_an_error() {
+        assert!(PixelBuffer::new(u32::MAX, u32::MAX).is_err());
+        assert_eq!(PixelBuffer::new(0, 5).map(|image| image.pixels.len()).ok(), Some(0));
+    }
+
+    #[test]
+    fn images_crop_to_a_rectangle() {
+        let mut image = PixelBuffer::new(3, 2).unwrap();
+        for (i, pixel) in image.pixels.iter_mut().enumerate() {
+            *pixel = [i as u8; 4];
+        }
+        let right = image.crop(Rect::new(1, 0, 2, 2)).unwrap();
+        assert_eq!(right.pixels, vec![[1; 4], [2; 4], [4; 4], [5; 4]]);
+        assert_eq!(right.row(1), &[[4; 4], [5; 4]]);
+        assert_eq!(image.crop(Rect::new(2, 0, 2, 2)), None);
+        assert_eq!(image.crop(Rect::new(0, -1, 1, 1)), None);
+        assert_eq!(image.crop(Rect::new(i32::MAX, 0, 1, 1)), None);
+    }
+
+    #[test]
+    fn colors_can_be_keyed_by_hand() {
+        let cyan = [0x00, 0xff, 0xff, 0xff];
+        let mut image = PixelBuffer::new(2, 2).unwrap();
+        image.pixels = vec![cyan, [1, 2, 3, 255], cyan, [0, 0xff, 0xff, 0x80]];
+        // Only exact matches; the half-transparent cyan stays
+        assert_eq!(image.replace(cyan, [0; 4]), 2);
+        assert_eq!(image.get(0, 1), [0; 4]);
+        assert_eq!(image.get(1, 1), [0, 0xff, 0xff, 0x80]);
+        image.map(|[r, g, b, a]| [255 - r, 255 - g, 255 - b, a]);
+        image.set(0, 0, [9; 4]);
+        assert_eq!(image.pixels, vec![[9; 4], [254, 253, 252, 255], [255, 255, 255, 0],
+                                      [255, 0, 0, 0x80]]);
+    }
+}
@@ -0,0 +1,325 @@
+//! Tile maps: levels built out of a grid of equally sized tiles, all
+//! cut from one sprite sheet.
+//!
+//! A level is a RON file naming its tile sheet, the clips on the sheet
+//! its tiles are (as described in the sheet's own data file, see
+//! `sprites`), which of them are solid, and then the map itself: a row
+//! of numbers per line, each a position in `tiles`, like Lazy Foo's
+//! `lazy.map`:
+//!
+//! ```text
+//! (
+//!     sheet: "tiles.png",
+//!     tiles: ["red", "green", "wall"],
+//!     solid: ["wall"],
+//!     map: "
+//!         00 01 02
+//!         02 00 01
+//!     ",
+//! )
+//! ```
+//!
+//! The sheet is looked for next to the level file.  Every row has to
+//! be as long as the first, and every tile the same size, which is
+//! checked when the level is loaded.
+
+use std::path::Path;
+
+use ron;
+use sdl2::rect::{self, Rect};
+use sdl2::render::Renderer;
+
+use error::{Error, Result};
+use resources;
+use sprites::SpriteSheet;
+
+/// The contents of a level file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct LevelFile {
+    pub sheet: String,
+    pub tiles: Vec<String>,
+    #[serde(default)]
+    pub solid: Vec<String>,
+    pub map: String,
+}
+
+impl LevelFile {
+
+    pub fn parse(text: &str) -> ::std::result::Result<LevelFile, String> {
+        ron::from_str(text).map_err(|err| err.to_string())
+    }
+}
+
+/// Which tile is where, and which tiles can't be walked through.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileMap {
+    pub columns: u32,
+    pub rows: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    // Each tile's clip on the sheet, and whether it is solid, by tile
+    // number
+    clips: Vec<Rect>,
+    solid: Vec<bool>,
+    // The tile number in each cell, a row at a time from the top
+    cells: Vec<usize>,
+}
+
+impl TileMap {
+
+    /// Lay out `level`'s map, looking up each of its tiles with `clip`.
+    pub fn new<F>(level: &LevelFile, clip: F) -> ::std::result::Result<TileMap, String>
+        where F: Fn(&str) -> Option<Rect>
+    {
+        let clips = level.tiles.iter()
+            .map(|name| clip(name).ok_or_else(|| format!("tile '{}' is not on the sheet", name)))
+            .collect::<::std::result::Result<Vec<Rect>, String>>()?;
+        let (tile_width, tile_height) = match clips.first() {
+            Some(first) => (first.width(), first.height()),
+            None => return Err("there are no tiles".to_string()),
+        };
+        if let Some((name, other)) = level.tiles.iter().zip(&clips)
+            .find(|&(_, other)| other.width() != tile_width || other.height() != tile_height) {
+            return Err(format!("tile '{}' is {}x{}, but the first tile is {}x{}",
+                               name, other.width(), other.height(), tile_width, tile_height));
+        }
+        for name in &level.solid {
+            if !level.tiles.contains(name) {
+                return Err(format!("solid tile '{}' is not one of the tiles", name));
+            }
+        }
+        let solid = level.tiles.iter().map(|name| level.solid.contains(name)).collect();
+
+        let mut cells = Vec::new();
+        let mut columns = None;
+        let rows = level.map.lines().filter(|line| !line.trim().is_empty()).enumerate();
+        for (row, line) in rows {
+            let start = cells.len();
+            for (column, number) in line.split_whitespace().enumerate() {
+                let tile = number.parse::<usize>().ok()
+                    .filter(|&tile| tile < clips.len())
+                    .ok_or_else(|| format!("row {}, column {}: '{}' is not a tile number \
+                                            (expected 0 to {})",
+                                           row + 1, column + 1, number, clips.len() - 1))?;
+                cells.push(tile);
+            }
+            let length = cells.len() - start;
+            match columns {
+                None => columns = Some(length),
+                Some(expected) if expected != length =>
+                    return Err(format!("row {} has {} tiles, but the first row has {}",
+                                       row + 1, length, expected)),
+                _ => {},
+            }
+        }
+        let columns = match columns {
+            Some(columns) => columns,
+            None => return Err("the map is empty".to_string()),
+        };
+        // Everything is placed with Rects, which can't go past
+        // max_int_value, so the whole level has to fit inside that
+        let rows = cells.len() / columns;
+        let fits = |count: usize, size: u32| count.checked_mul(size as usize)
+            .is_some_and(|pixels| pixels <= rect::max_int_value() as usize);
+        if !fits(columns, tile_width) || !fits(rows, tile_height) {
+            return Err(format!("a {}x{} map of {}x{} tiles is too big to draw",
+                               columns, rows, tile_width, tile_height));
+        }
+        Ok(TileMap {
+            columns: columns as u32,
+            rows: rows as u32,
+            tile_width,
+            tile_height,
+            clips,
+            solid,
+            cells,
+        })
+    }
+
+    /// How wide the whole level is, in pixels.
+    pub fn width(&self) -> u32 {
+        self.columns * self.tile_width
+    }
+
+    /// How tall the whole level is, in pixels.
+    pub fn height(&self) -> u32 {
+        self.rows * self.tile_height
+    }
+
+    /// The tile number at `column`, `row`, if that is on the map.
+    pub fn tile(&self, column: u32, row: u32) -> Option<usize> {
+        if column < self.columns && row < self.rows {
+            Some(self.cells[row as usize * self.columns as usize + column as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Whether the tile at `column`, `row` is solid.  Off the edge of
+    /// the map counts as solid, so nothing can leave it.
+    pub fn is_solid(&self, column: i32, row: i32) -> bool {
+        if column < 0 || row < 0 {
+            return true;
+        }
+        self.tile(column as u32, row as u32).is_none_or(|tile| self.solid[tile])
+    }
+
+    // The columns and rows of the cells `area` covers, some of which
+    // may be off the map
+    fn cells_under(&self, area: Rect) -> (i32, i32, i32, i32) {
+        let (tile_width, tile_height) = (self.tile_width as i32, self.tile_height as i32);
+        (area.left().div_euclid(tile_width), (area.right() - 1).div_euclid(tile_width),
+         area.top().div_euclid(tile_height), (area.bottom() - 1).div_euclid(tile_height))
+    }
+
+    /// Whether `area` (in level coordinates) overlaps a solid tile or
+    /// hangs off the edge of the map.  Only the tiles under it are
+    /// looked at, however big the map is.
+    pub fn touches_solid(&self, area: Rect) -> bool {
+        let (left, right, top, bottom) = self.cells_under(area);
+        (top..=bottom).any(|row| (left..=right).any(|column| self.is_solid(column, row)))
+    }
+
+    /// The tiles that can be seen through `camera`: where each one's
+    /// clip is on the sheet, and where it goes in level coordinates.
+    /// Tiles outside the camera are skipped, so drawing a big level
+    /// costs no more than a small one.
+    pub fn visible(&self, camera: Rect) -> Vec<(Rect, Rect)> {
+        let (left, right, top, bottom) = self.cells_under(camera);
+        let columns = left.max(0) as u32..(right + 1).clamp(0, self.columns as i32) as u32;
+        let rows = top.max(0) as u32..(bottom + 1).clamp(0, self.rows as i32) as u32;
+        rows.flat_map(|row| columns.clone().map(move |column| (column, row)))
+            .filter_map(|(column, row)| self.tile(column, row).map(|tile| {
+                (self.clips[tile],
+                 Rect::new((column * self.tile_width) as i32, (row * self.tile_height) as i32,
+                           self.tile_width, self.tile_height))
+            }))
+            .collect()
+    }
+}
+
+/// A tile map, and the sheet its tiles are drawn from.
+pub struct Level {
+    pub sheet: SpriteSheet,
+    pub map: TileMap,
+}
+
+impl Level {
+
+    /// Load the level file at `path`, and the tile sheet it names.
+    pub fn load(renderer: &Renderer, path: &Path) -> Result<Level> {
+        let text = resources::read_to_string(path)?;
+        let file = LevelFile::parse(&text).map_err(|err| Error::data(path, err))?;
+        let sheet = SpriteSheet::load(renderer, &path.with_file_name(&file.sheet))?;
+        let map = TileMap::new(&file, |name| sheet.clip(name).ok())
+            .map_err(|err| Error::data(path, err))?;
+        Ok(Level { sheet, map })
+    }
+
+    /// Draw the part of the level `camera` can see, with the camera's
+    /// top left corner at the top left of the screen.
+    pub fn render(&self, renderer: &mut Renderer, camera: Rect) -> Result<()> {
+        for (clip, place) in self.map.visible(camera) {
+            self.sheet.texture.render(renderer, place.x() - camera.x(), place.y() - camera.y(),
+                                      Some(clip))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: &str = r#"(
+        sheet: "tiles.png",
+        tiles: ["floor", "wall"],
+        solid: ["wall"],
+        map: "
+            00 00 00
+            00 01 00
+        ",
+    )"#;
+
+    // Every tile is 10x10, side by side on the sheet
+    fn clip(name: &str) -> Option<Rect> {
+        match name {
+            "floor" => Some(Rect::new(0, 0, 10, 10)),
+            "wall" => Some(Rect::new(10, 0, 10, 10)),
+            "big" => Some(Rect::new(20, 0, 20, 20)),
+            "huge" => Some(Rect::new(0, 0, 1 << 28, 1 << 28)),
+            _ => None,
+        }
+    }
+
+    fn map(text: &str) -> ::std::result::Result<TileMap, String> {
+        TileMap::new(&LevelFile::parse(text)?, clip)
+    }
+
+    #[test]
+    fn maps_are_laid_out_in_rows() {
+        let map = map(LEVEL).unwrap();
+        assert_eq!((map.columns, map.rows), (3, 2));
+        assert_eq!((map.width(), map.height()), (30, 20));
+        assert_eq!(map.tile(1, 1), Some(1));
+        assert_eq!(map.tile(3, 0), None);
+    }
+
+    #[test]
+    fn bad_maps_are_rejected() {
+        let err = map(&LEVEL.replace("00 01 00", "00 01")).unwrap_err();
+        assert!(err.starts_with("row 2"), "{}", err);
+        let err = map(&LEVEL.replace("00 01 00", "00 02 00")).unwrap_err();
+        assert!(err.contains("'02'"), "{}", err);
+        assert!(map(&LEVEL.replace("\"wall\"]", "\"big\"]")).is_err());
+        assert!(map(&LEVEL.replace("solid: [\"wall\"]", "solid: [\"lava\"]")).is_err());
+        assert!(map(&LEVEL.replace("\"floor\",", "\"carpet\",")).is_err());
+    }
+
+    #[test]
+    fn levels_too_big_to_draw_are_rejected() {
+        let huge = LEVEL.replace("[\"floor\", \"wall\"]", "[\"huge\"]")
+            .replace("solid: [\"wall\"]", "solid: []");
+        let map_of = |rows: &[&str]| map(&huge.replace("00 00 00\n            00 01 00",
+                                                      &rows.join("\n")));
+        assert!(map_of(&["00 00 00"]).is_ok());
+        let err = map_of(&["00 00 00 00"]).unwrap_err();
+        assert!(err.contains("too big"), "{}", err);
+        assert!(map_of(&["00"; 4]).is_err());
+        assert!(map_of(&["00 ".repeat(17).trim()]).is_err());
+    }
+
+    #[test]
+    fn solid_tiles_and_edges_block() {
+        let map = map(LEVEL).unwrap();
+        assert!(map.touches_solid(Rect::new(8, 8, 4, 4)));
+        assert!(!map.touches_solid(Rect::new(0, 0, 10, 10)));
+        assert!(!map.touches_solid(Rect::new(20, 10, 10, 10)));
+        assert!(map.touches_solid(Rect::new(-1, 0, 5, 5)));
+        assert!(map.touches_solid(Rect::new(26, 0, 5, 5)));
+    }
+
+    #[test]
+    fn only_visible_tiles_are_drawn() {
+        let map = map(LEVEL).unwrap();
+        assert_eq!(map.visible(Rect::new(0, 0, 30, 20)).len(), 6);
+        assert_eq!(map.visible(Rect::new(15, 5, 10, 10)),
+                   vec![(clip("floor").unwrap(), Rect::new(10, 0, 10, 10)),
+                        (clip("floor").unwrap(), Rect::new(20, 0, 10, 10)),
+                        (clip("wall").unwrap(), Rect::new(10, 10, 10, 10)),
+                        (clip("floor").unwrap(), Rect::new(20, 10, 10, 10))]);
+        assert!(map.visible(Rect::new(-50, -50, 20, 20)).is_empty());
+    }
+
+    #[test]
+    fn shipped_levels_fit_their_sheets() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let text = ::std::fs::read_to_string(root.join("resources/lazy_map.ron")).unwrap();
+        let level = LevelFile::parse(&text).unwrap();
+        let sheet_path = root.join("resources").join(&level.sheet);
+        let text = ::std::fs::read_to_string(::sprites::descriptor_path(&sheet_path)).unwrap();
+        let clips = ::sprites::Descriptor::parse(&text).unwrap().frames(320, 240).unwrap();
+        let map = TileMap::new(&level, |name| clips.get(name).map(|frames| frames[0])).unwrap();
+        assert_eq!((map.width(), map.height()), (1280, 960));
+    }
+}
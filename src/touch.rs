@@ -0,0 +1,395 @@
+//! Touches and multi-finger gestures, on a desktop.
+//!
+//! Lazy Foo's touch lessons are written for phones, but SDL reports
+//! touchscreens and touchpads on desktop platforms the same way:
+//! `FingerDown`, `FingerMotion` and `FingerUp` for each finger, with
+//! `x` and `y` running from 0 to 1 across the device, and while two or
+//! more fingers are down, `MultiGesture` events saying how far they
+//! have turned (`d_theta`, in radians) and pinched (`d_dist`) since the
+//! last one.  `Touches` keeps track of the fingers that are down, and
+//! `Pinch` adds the gestures up into a rotation and a scale.
+//!
+//! Most desktops have no touchscreen, so `MouseTouch` makes touches
+//! out of the mouse.  Dragging with the left button is one finger.
+//! Dragging with the right button is two, the second mirrored through
+//! the middle of the window, which is how phone emulators fake a pinch:
+//! moving the mouse around the middle turns them, and towards or away
+//! from it pinches them, with `MultiGesture` events worked out the way
+//! SDL works out its own.
+//!
+//! rust-sdl2 can't push finger events onto SDL's queue, so tests hand
+//! them to a lesson in a `--replay` recording instead (see
+//! `src/replay.rs`).
+
+use std::f32::consts::PI;
+
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+
+/// The touch device id `MouseTouch` gives its touches.  SDL 2.0.10 and
+/// later use the same id for touches it makes from the mouse.
+pub const MOUSE_TOUCH_ID: i64 = -1;
+
+/// The `which` of the mouse events SDL makes from touches, so that
+/// programs that only know about the mouse still work with a finger.
+/// `MouseTouch` ignores them, rather than turning them back into
+/// touches.
+pub const TOUCH_MOUSE_ID: u32 = 0xffff_ffff;
+
+// How much bigger a pinch makes things: pinching the fingers apart by
+// a tenth of the device makes them 40% bigger
+const PINCH_SCALE: f32 = 4.0;
+const MIN_SCALE: f32 = 0.1;
+
+/// A finger that is down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Touch {
+    pub touch_id: i64,
+    pub finger_id: i64,
+    /// Where it is, from 0 to 1 across and down the device
+    pub x: f32,
+    pub y: f32,
+    /// From 0 to 1; devices that can't tell say 1
+    pub pressure: f32,
+}
+
+/// The fingers that are down, in the order they went down.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Touches {
+    fingers: Vec<Touch>,
+}
+
+impl Touches {
+
+    pub fn new() -> Touches {
+        Touches::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fingers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fingers.is_empty()
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<'_, Touch> {
+        self.fingers.iter()
+    }
+
+    pub fn get(&self, touch_id: i64, finger_id: i64) -> Option<&Touch> {
+        self.fingers.iter()
+            .find(|touch| touch.touch_id == touch_id && touch.finger_id == finger_id)
+    }
+
+    /// Follow a finger event.  Returns true if a finger went down, moved
+    /// or came up; other events are ignored.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::FingerDown { touch_id, finger_id, x, y, pressure, .. } |
+            Event::FingerMotion { touch_id, finger_id, x, y, pressure, .. } => {
+                let touch = Touch { touch_id, finger_id, x, y, pressure };
+                match self.fingers.iter_mut()
+                    .find(|down| down.touch_id == touch_id && down.finger_id == finger_id) {
+                    Some(down) => *down = touch,
+                    None => self.fingers.push(touch),
+                }
+                true
+            },
+            Event::FingerUp { touch_id, finger_id, .. } => {
+                let before = self.fingers.len();
+                self.fingers.retain(|down| down.touch_id != touch_id || down.finger_id != finger_id);
+                self.fingers.len() != before
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Multi-finger gestures, added up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pinch {
+    rotation: f32,
+    distance: f32,
+    centre: (f32, f32),
+    fingers: u16,
+}
+
+impl Pinch {
+
+    /// No turn, no pinch, centred on the device.
+    pub fn new() -> Pinch {
+        Pinch { rotation: 0.0, distance: 0.0, centre: (0.5, 0.5), fingers: 0 }
+    }
+
+    /// How far the fingers have turned, in degrees clockwise.
+    pub fn rotation_degrees(&self) -> f64 {
+        f64::from(self.rotation).to_degrees()
+    }
+
+    /// How much bigger (or, below 1, smaller) the pinches so far make
+    /// things.
+    pub fn scale(&self) -> f32 {
+        (1.0 + self.distance * PINCH_SCALE).max(MIN_SCALE)
+    }
+
+    /// The middle of the fingers in the latest gesture, from 0 to 1.
+    pub fn centre(&self) -> (f32, f32) {
+        self.centre
+    }
+
+    /// How many fingers the latest gesture had.
+    pub fn fingers(&self) -> u16 {
+        self.fingers
+    }
+
+    /// Add up a `MultiGesture` event.  Returns true if it was one.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::MultiGesture { d_theta, d_dist, x, y, num_fingers, .. } => {
+                self.rotation += d_theta;
+                self.distance += d_dist;
+                self.centre = (x, y);
+                self.fingers = num_fingers;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Default for Pinch {
+    fn default() -> Pinch {
+        Pinch::new()
+    }
+}
+
+// Which finger event to make
+#[derive(Clone, Copy)]
+enum Phase {
+    Down,
+    Motion,
+    Up,
+}
+
+// A finger event for one of the mouse's fingers, at full pressure
+fn finger_event(phase: Phase, timestamp: u32, finger_id: i64, (x, y): (f32, f32),
+                (dx, dy): (f32, f32)) -> Event {
+    let touch_id = MOUSE_TOUCH_ID;
+    let pressure = 1.0;
+    match phase {
+        Phase::Down => Event::FingerDown { timestamp, touch_id, finger_id, x, y, dx, dy, pressure },
+        Phase::Motion => Event::FingerMotion { timestamp, touch_id, finger_id, x, y, dx, dy, pressure },
+        Phase::Up => Event::FingerUp { timestamp, touch_id, finger_id, x, y, dx, dy, pressure },
+    }
+}
+
+/// Turns mouse drags into touches.
+#[derive(Clone, Debug)]
+pub struct MouseTouch {
+    width: u32,
+    height: u32,
+    enabled: bool,
+    // The button being held down, and where the first finger is
+    held: Option<MouseButton>,
+    last: (f32, f32),
+}
+
+impl MouseTouch {
+
+    /// Emulate touches on a `width` x `height` window, measured the
+    /// way its mouse events are (in its logical size, if it has one).
+    pub fn new(width: u32, height: u32) -> MouseTouch {
+        MouseTouch { width, height, enabled: true, held: None, last: (0.0, 0.0) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn the emulation on or off.  Turning it off lifts any fingers
+    /// that are down, so the events to do that are returned.
+    pub fn set_enabled(&mut self, enabled: bool) -> Vec<Event> {
+        let lifted = if enabled { Vec::new() } else { self.lift(0) };
+        self.enabled = enabled;
+        lifted
+    }
+
+    // From the window's pixels to 0-1, as touches are measured
+    fn normalize(&self, x: i32, y: i32) -> (f32, f32) {
+        ((x as f32 / self.width as f32).clamp(0.0, 1.0),
+         (y as f32 / self.height as f32).clamp(0.0, 1.0))
+    }
+
+    // Where the second finger of a two finger drag is
+    fn mirror((x, y): (f32, f32)) -> (f32, f32) {
+        (1.0 - x, 1.0 - y)
+    }
+
+    // Where the first finger is, as an angle and a distance from the
+    // middle; the second is always the same distance the other side
+    fn polar((x, y): (f32, f32)) -> (f32, f32) {
+        let (dx, dy) = (x - 0.5, y - 0.5);
+        (dy.atan2(dx), (dx * dx + dy * dy).sqrt())
+    }
+
+    // The first finger and, for the right button, the second
+    fn fingers(&self, held: MouseButton, phase: Phase, timestamp: u32) -> Vec<Event> {
+        let mut events = vec![finger_event(phase, timestamp, 0, self.last, (0.0, 0.0))];
+        if held == MouseButton::Right {
+            events.push(finger_event(phase, timestamp, 1, MouseTouch::mirror(self.last),
+                                     (0.0, 0.0)));
+        }
+        events
+    }
+
+    fn lift(&mut self, timestamp: u32) -> Vec<Event> {
+        match self.held.take() {
+            Some(held) => self.fingers(held, Phase::Up, timestamp),
+            None => Vec::new(),
+        }
+    }
+
+    /// The touch events a mouse event stands for: none, if it isn't a
+    /// left or right button drag or the emulation is off.
+    pub fn translate(&mut self, event: &Event) -> Vec<Event> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        match *event {
+            Event::MouseButtonDown { timestamp, which, mouse_btn, x, y, .. }
+                if which != TOUCH_MOUSE_ID && self.held.is_none() &&
+                   (mouse_btn == MouseButton::Left || mouse_btn == MouseButton::Right) => {
+                self.held = Some(mouse_btn);
+                self.last = self.normalize(x, y);
+                self.fingers(mouse_btn, Phase::Down, timestamp)
+            },
+            Event::MouseMotion { timestamp, which, x, y, .. }
+                if which != TOUCH_MOUSE_ID && self.held.is_some() => {
+                let now = self.normalize(x, y);
+                let before = self.last;
+                self.last = now;
+                let moved = (now.0 - before.0, now.1 - before.1);
+                let mut events = vec![finger_event(Phase::Motion, timestamp, 0, now, moved)];
+                if self.held == Some(MouseButton::Right) {
+                    events.push(finger_event(Phase::Motion, timestamp, 1, MouseTouch::mirror(now),
+                                             (-moved.0, -moved.1)));
+                    let (angle_before, distance_before) = MouseTouch::polar(before);
+                    let (angle, distance) = MouseTouch::polar(now);
+                    // Turning past half way round is the short way back
+                    let mut d_theta = angle - angle_before;
+                    if d_theta > PI {
+                        d_theta -= 2.0 * PI;
+                    } else if d_theta < -PI {
+                        d_theta += 2.0 * PI;
+                    }
+                    events.push(Event::MultiGesture {
+                        timestamp, touch_id: MOUSE_TOUCH_ID, d_theta,
+                        d_dist: distance - distance_before, x: 0.5, y: 0.5, num_fingers: 2,
+                    });
+                }
+                events
+            },
+            Event::MouseButtonUp { timestamp, which, mouse_btn, .. }
+                if which != TOUCH_MOUSE_ID && self.held == Some(mouse_btn) => self.lift(timestamp),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::mouse::MouseState;
+
+    fn press(mouse_btn: MouseButton, x: i32, y: i32) -> Event {
+        Event::MouseButtonDown { timestamp: 0, window_id: 1, which: 0, mouse_btn, x, y }
+    }
+
+    fn release(mouse_btn: MouseButton, x: i32, y: i32) -> Event {
+        Event::MouseButtonUp { timestamp: 0, window_id: 1, which: 0, mouse_btn, x, y }
+    }
+
+    fn drag(x: i32, y: i32) -> Event {
+        Event::MouseMotion { timestamp: 0, window_id: 1, which: 0,
+                             mousestate: MouseState::from_sdl_state(1), x, y, xrel: 0, yrel: 0 }
+    }
+
+    #[test]
+    fn touches_follow_fingers() {
+        let mut touches = Touches::new();
+        let finger = |finger_id, x| Event::FingerDown {
+            timestamp: 0, touch_id: 7, finger_id, x, y: 0.5, dx: 0.0, dy: 0.0, pressure: 1.0,
+        };
+        assert!(touches.handle_event(&finger(1, 0.25)));
+        assert!(touches.handle_event(&finger(2, 0.75)));
+        assert!(touches.handle_event(&Event::FingerMotion {
+            timestamp: 0, touch_id: 7, finger_id: 1, x: 0.5, y: 0.5, dx: 0.25, dy: 0.0,
+            pressure: 0.5,
+        }));
+        assert_eq!(touches.len(), 2);
+        assert_eq!(touches.get(7, 1).map(|touch| (touch.x, touch.pressure)), Some((0.5, 0.5)));
+        assert!(touches.handle_event(&Event::FingerUp {
+            timestamp: 0, touch_id: 7, finger_id: 2, x: 0.75, y: 0.5, dx: 0.0, dy: 0.0,
+            pressure: 0.0,
+        }));
+        assert_eq!(touches.iter().map(|touch| touch.finger_id).collect::<Vec<_>>(), vec![1]);
+        assert!(!touches.handle_event(&press(MouseButton::Left, 0, 0)));
+    }
+
+    #[test]
+    fn the_mouse_makes_touches() {
+        let mut mouse = MouseTouch::new(200, 100);
+        let mut touches = Touches::new();
+        for event in mouse.translate(&press(MouseButton::Left, 50, 50)) {
+            touches.handle_event(&event);
+        }
+        assert_eq!(touches.get(MOUSE_TOUCH_ID, 0).map(|touch| (touch.x, touch.y)),
+                   Some((0.25, 0.5)));
+        // Another button while one is held does nothing
+        assert!(mouse.translate(&press(MouseButton::Right, 0, 0)).is_empty());
+        for event in mouse.translate(&drag(100, 0)) {
+            touches.handle_event(&event);
+        }
+        assert_eq!(touches.get(MOUSE_TOUCH_ID, 0).map(|touch| (touch.x, touch.y)),
+                   Some((0.5, 0.0)));
+        for event in mouse.translate(&release(MouseButton::Left, 100, 0)) {
+            touches.handle_event(&event);
+        }
+        assert!(touches.is_empty());
+
+        // SDL's mouse events from real touches aren't turned back into
+        // touches, and neither is anything once it's turned off
+        let from_touch = Event::MouseButtonDown { timestamp: 0, window_id: 1, which: TOUCH_MOUSE_ID,
+                                                  mouse_btn: MouseButton::Left, x: 0, y: 0 };
+        assert!(mouse.translate(&from_touch).is_empty());
+        mouse.translate(&press(MouseButton::Left, 0, 0));
+        assert_eq!(mouse.set_enabled(false).len(), 1);
+        assert!(mouse.translate(&press(MouseButton::Left, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn right_drags_pinch_and_turn() {
+        let mut mouse = MouseTouch::new(200, 200);
+        let mut touches = Touches::new();
+        let mut pinch = Pinch::new();
+        // Start a quarter of the way across, level with the middle,
+        // then go a quarter turn round and twice as far out
+        for event in &[press(MouseButton::Right, 50, 100), drag(100, 200)] {
+            for touch in mouse.translate(event) {
+                touches.handle_event(&touch);
+                pinch.handle_event(&touch);
+            }
+        }
+        assert_eq!(touches.len(), 2);
+        assert_eq!(touches.get(MOUSE_TOUCH_ID, 1).map(|touch| (touch.x, touch.y)),
+                   Some((0.5, 0.0)));
+        assert!((pinch.rotation_degrees() + 90.0).abs() < 0.01);
+        assert!((pinch.scale() - 2.0).abs() < 0.01);
+        assert_eq!(pinch.fingers(), 2);
+        for touch in mouse.translate(&release(MouseButton::Right, 100, 200)) {
+            touches.handle_event(&touch);
+        }
+        assert!(touches.is_empty());
+    }
+}
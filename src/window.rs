@@ -0,0 +1,633 @@
+//! Keeping up with what the window manager does to a window.
+//!
+//! The other lessons' windows stay 640x480 and in front, because they
+//! can't be resized and are scaled if they start out any other size.
+//! A window that can be resized, minimized or covered up gets told
+//! about it through `Event::Window`, and `WindowState` follows those
+//! events so a lesson can ask:
+//!
+//! * how big the window is now, to lay itself out again (`size`)
+//! * whether there is any point drawing (`is_visible`); nothing shows
+//!   while the window is minimized or hidden
+//! * whether the mouse is over it and whether it has the keyboard
+//!   (`has_mouse_focus`, `has_keyboard_focus`)
+//!
+//! Each window has an id, and the events carry the id of the window
+//! they are about, so with more than one window each `WindowState`
+//! only listens to its own.
+//!
+//! `WindowManager` looks after several windows at once, each with its
+//! own renderer and its own `Assets` - a texture belongs to the
+//! renderer that made it, so windows can't share them.  It hands each
+//! event to the window it is about, keeps track of which window has
+//! the focus, and hides a window when its close button is clicked
+//! rather than ending the lesson.  The bookkeeping for that is done by
+//! a `WindowStates`, which knows nothing about renderers:
+//!
+//! ```text
+//! let main = windows.add("main", renderer, Assets::new());
+//! ...
+//! for event in events.poll(&mut event_pump) {
+//!     windows.handle_event(&event);
+//! }
+//! if windows.all_hidden() { running = false; }
+//! for window in windows.visible_mut() { ... }
+//! ```
+
+use std::slice;
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::render::Renderer;
+use sdl2::video::{FullscreenType, WindowRef};
+
+use assets::Assets;
+use error::{Error, Result};
+
+/// A window, as far as its events say.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowState {
+    id: u32,
+    width: u32,
+    height: u32,
+    position: (i32, i32),
+    shown: bool,
+    minimized: bool,
+    maximized: bool,
+    mouse_focus: bool,
+    keyboard_focus: bool,
+    close_requested: bool,
+}
+
+impl WindowState {
+
+    /// A window that is `width` x `height`, shown and has the focus,
+    /// which is how a newly created window usually starts out.
+    pub fn new(id: u32, width: u32, height: u32) -> WindowState {
+        WindowState {
+            id,
+            width,
+            height,
+            position: (0, 0),
+            shown: true,
+            minimized: false,
+            maximized: false,
+            mouse_focus: true,
+            keyboard_focus: true,
+            close_requested: false,
+        }
+    }
+
+    /// Start from the window as it is now.
+    pub fn from_window(window: &WindowRef) -> WindowState {
+        let (width, height) = window.size();
+        let mut state = WindowState::new(window.id(), width, height);
+        state.position = window.position();
+        state
+    }
+
+    /// The id of the window, as its events give it.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Where the window's top left corner is on the desktop.
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// False while the window is minimized or hidden, when there's no
+    /// point drawing anything.
+    pub fn is_visible(&self) -> bool {
+        self.shown && !self.minimized
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        self.maximized
+    }
+
+    /// True while the mouse is over the window.
+    pub fn has_mouse_focus(&self) -> bool {
+        self.mouse_focus
+    }
+
+    /// True while key presses go to the window.
+    pub fn has_keyboard_focus(&self) -> bool {
+        self.keyboard_focus
+    }
+
+    /// True once the window's close button has been clicked.  With one
+    /// window SDL sends `Event::Quit` as well; with more, this is the
+    /// only sign.
+    pub fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    /// Follow a window event for this window.  Returns true if the
+    /// window needs drawing again: it changed size or focus, was
+    /// restored, or has been uncovered (`Exposed`).  Events for other
+    /// windows, and everything else, are ignored.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        let win_event = match *event {
+            Event::Window { window_id, ref win_event, .. } if window_id == self.id => win_event,
+            _ => return false,
+        };
+        match *win_event {
+            WindowEvent::Shown => self.shown = true,
+            WindowEvent::Hidden => self.shown = false,
+            WindowEvent::Exposed => {},
+            WindowEvent::Moved(x, y) => {
+                self.position = (x, y);
+                return false;
+            },
+            WindowEvent::Resized(width, height) | WindowEvent::SizeChanged(width, height) => {
+                self.width = width.max(0) as u32;
+                self.height = height.max(0) as u32;
+            },
+            WindowEvent::Minimized => self.minimized = true,
+            WindowEvent::Maximized => {
+                self.minimized = false;
+                self.maximized = true;
+            },
+            WindowEvent::Restored => {
+                self.minimized = false;
+                self.maximized = false;
+            },
+            WindowEvent::Enter => self.mouse_focus = true,
+            WindowEvent::Leave => self.mouse_focus = false,
+            WindowEvent::FocusGained => self.keyboard_focus = true,
+            WindowEvent::FocusLost => self.keyboard_focus = false,
+            WindowEvent::Close => {
+                self.close_requested = true;
+                return false;
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Switch `window` into `mode`, or back to a window if it is already
+/// in it.  Returns the mode it ends up in.
+pub fn toggle_fullscreen(window: &mut WindowRef, mode: FullscreenType) -> Result<FullscreenType> {
+    let next = if window.fullscreen_state() == mode { FullscreenType::Off } else { mode };
+    window.set_fullscreen(next).map_err(Error::video)?;
+    Ok(next)
+}
+
+/// The id of the window `event` happened in, for the events that
+/// carry one: window, keyboard, text and mouse events.
+pub fn event_window_id(event: &Event) -> Option<u32> {
+    match *event {
+        Event::Window { window_id, .. } |
+        Event::KeyDown { window_id, .. } |
+        Event::KeyUp { window_id, .. } |
+        Event::TextEditing { window_id, .. } |
+        Event::TextInput { window_id, .. } |
+        Event::MouseMotion { window_id, .. } |
+        Event::MouseButtonDown { window_id, .. } |
+        Event::MouseButtonUp { window_id, .. } |
+        Event::MouseWheel { window_id, .. } => Some(window_id),
+        _ => None,
+    }
+}
+
+/// What the events say about several windows, in the order they were
+/// added: which one has the mouse, which the keyboard, and which are
+/// showing.  This is the bookkeeping half of `WindowManager`, kept
+/// apart from the renderers so that it can be tested with made-up
+/// events.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WindowStates {
+    states: Vec<WindowState>,
+}
+
+/// Where `WindowStates::handle_event` sent an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Routed {
+    /// The window the event was about
+    pub index: usize,
+    /// True if it closed the window, which is now hidden
+    pub closed: bool,
+}
+
+impl WindowStates {
+
+    pub fn new() -> WindowStates {
+        WindowStates { states: Vec::new() }
+    }
+
+    /// Keep track of a window from now on, returning its index.  A
+    /// window that has just been made takes the focus, so the others
+    /// lose it.
+    pub fn add(&mut self, state: WindowState) -> usize {
+        for other in &mut self.states {
+            other.mouse_focus = false;
+            other.keyboard_focus = false;
+        }
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&WindowState> {
+        self.states.get(index)
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, WindowState> {
+        self.states.iter()
+    }
+
+    /// The index of the window whose id is `id`.
+    pub fn find_id(&self, id: u32) -> Option<usize> {
+        self.states.iter().position(|state| state.id == id)
+    }
+
+    /// The index of the window key presses are going to, if it is one
+    /// of these.
+    pub fn focused(&self) -> Option<usize> {
+        self.states.iter().position(|state| state.is_visible() && state.has_keyboard_focus())
+    }
+
+    /// True if any of the windows can be seen.
+    pub fn any_visible(&self) -> bool {
+        self.states.iter().any(WindowState::is_visible)
+    }
+
+    /// True once every window has been closed or hidden.  Minimized
+    /// windows don't count as hidden.
+    pub fn all_hidden(&self) -> bool {
+        self.states.iter().all(|state| !state.shown)
+    }
+
+    /// Note window `index` being shown, brought to the front and given
+    /// the focus.  False if there is no such window.
+    pub fn show(&mut self, index: usize) -> bool {
+        if index >= self.states.len() {
+            return false;
+        }
+        for other in &mut self.states {
+            other.keyboard_focus = false;
+        }
+        let state = &mut self.states[index];
+        state.shown = true;
+        state.minimized = false;
+        state.close_requested = false;
+        state.keyboard_focus = true;
+        true
+    }
+
+    /// Note window `index` being hidden.  False if there is no such
+    /// window.
+    pub fn hide(&mut self, index: usize) -> bool {
+        match self.states.get_mut(index) {
+            Some(state) => {
+                state.shown = false;
+                state.mouse_focus = false;
+                state.keyboard_focus = false;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Pass `event` on to the window it happened in.  Only one window
+    /// has the mouse, and one the keyboard, so one gaining either
+    /// takes it from the rest; clicking a window's close button hides
+    /// it.  Events that aren't about any one of these windows return
+    /// None.
+    pub fn handle_event(&mut self, event: &Event) -> Option<Routed> {
+        let index = self.find_id(event_window_id(event)?)?;
+        if let Event::Window { ref win_event, .. } = *event {
+            for (other, state) in self.states.iter_mut().enumerate() {
+                if other == index {
+                    continue;
+                }
+                match *win_event {
+                    WindowEvent::Enter => state.mouse_focus = false,
+                    WindowEvent::FocusGained => state.keyboard_focus = false,
+                    _ => {},
+                }
+            }
+        }
+        self.states[index].handle_event(event);
+        let closed = self.states[index].close_requested();
+        if closed {
+            self.hide(index);
+            self.states[index].close_requested = false;
+        }
+        Some(Routed { index, closed })
+    }
+}
+
+/// One of a `WindowManager`'s windows: its renderer, and the textures
+/// loaded for that renderer.
+pub struct ManagedWindow<'ttf> {
+    name: String,
+    pub renderer: Renderer<'static>,
+    pub assets: Assets<'ttf>,
+}
+
+impl<'ttf> ManagedWindow<'ttf> {
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Several windows, in the order they were added, and a `WindowStates`
+/// following what their events say.
+///
+/// `'ttf` is as for `Assets`: the lifetime of the SDL_ttf context any
+/// of the windows' fonts were loaded with.
+pub struct WindowManager<'ttf> {
+    windows: Vec<ManagedWindow<'ttf>>,
+    states: WindowStates,
+}
+
+impl<'ttf> WindowManager<'ttf> {
+
+    pub fn new() -> WindowManager<'ttf> {
+        WindowManager { windows: Vec::new(), states: WindowStates::new() }
+    }
+
+    /// Look after `renderer`'s window from now on, along with the
+    /// textures in `assets`.  Returns the window's index.  A window
+    /// that has just been made takes the focus, so the others lose it.
+    pub fn add(&mut self, name: &str, renderer: Renderer<'static>, assets: Assets<'ttf>) -> usize {
+        let state = match renderer.window() {
+            Some(window) => WindowState::from_window(window),
+            None => WindowState::new(0, 0, 0),
+        };
+        self.states.add(state);
+        self.windows.push(ManagedWindow { name: name.to_string(), renderer, assets });
+        self.windows.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&ManagedWindow<'ttf>> {
+        self.windows.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut ManagedWindow<'ttf>> {
+        self.windows.get_mut(index)
+    }
+
+    /// What the events have said about every window, by index.
+    pub fn states(&self) -> &WindowStates {
+        &self.states
+    }
+
+    /// What the events have said about window `index`.
+    pub fn state(&self, index: usize) -> Option<&WindowState> {
+        self.states.get(index)
+    }
+
+    /// False while window `index` is hidden or minimized, or if there
+    /// is no such window.
+    pub fn is_visible(&self, index: usize) -> bool {
+        self.states.get(index).is_some_and(WindowState::is_visible)
+    }
+
+    /// The index of the window called `name`.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.windows.iter().position(|window| window.name == name)
+    }
+
+    /// The index of the window whose id is `id`.
+    pub fn find_id(&self, id: u32) -> Option<usize> {
+        self.states.find_id(id)
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, ManagedWindow<'ttf>> {
+        self.windows.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, ManagedWindow<'ttf>> {
+        self.windows.iter_mut()
+    }
+
+    /// The windows that can be seen, for drawing.
+    pub fn visible_mut(&mut self) -> impl Iterator<Item = &mut ManagedWindow<'ttf>> {
+        self.windows.iter_mut()
+            .zip(self.states.iter())
+            .filter(|&(_, state)| state.is_visible())
+            .map(|(window, _)| window)
+    }
+
+    /// The index of the window key presses are going to, if it is one
+    /// of these.
+    pub fn focused(&self) -> Option<usize> {
+        self.states.focused()
+    }
+
+    /// True if any of the windows can be seen.
+    pub fn any_visible(&self) -> bool {
+        self.states.any_visible()
+    }
+
+    /// Show window `index`, bring it to the front and give it the
+    /// focus.  False if there is no such window.
+    pub fn show(&mut self, index: usize) -> bool {
+        if !self.states.show(index) {
+            return false;
+        }
+        if let Some(window) = self.windows[index].renderer.window_mut() {
+            window.show();
+            window.raise();
+        }
+        true
+    }
+
+    /// Hide window `index`.  It keeps its renderer and textures, ready
+    /// for `show`.  False if there is no such window.
+    pub fn hide(&mut self, index: usize) -> bool {
+        if !self.states.hide(index) {
+            return false;
+        }
+        self.hide_window(index);
+        true
+    }
+
+    // Hide the window itself, once its state says it is hidden
+    fn hide_window(&mut self, index: usize) {
+        if let Some(window) = self.windows[index].renderer.window_mut() {
+            window.hide();
+        }
+    }
+
+    /// True once every window has been closed or hidden: time for the
+    /// lesson to end.
+    pub fn all_hidden(&self) -> bool {
+        self.states.all_hidden()
+    }
+
+    /// Pass `event` on to the window it happened in, and return that
+    /// window's index.  Clicking a window's close button hides it.
+    /// Events that aren't about any one of these windows return None.
+    pub fn handle_event(&mut self, event: &Event) -> Option<usize> {
+        let routed = self.states.handle_event(event)?;
+        if routed.closed {
+            self.hide_window(routed.index);
+        }
+        Some(routed.index)
+    }
+}
+
+impl<'ttf> Default for WindowManager<'ttf> {
+    fn default() -> WindowManager<'ttf> {
+        WindowManager::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_event(window_id: u32, win_event: WindowEvent) -> Event {
+        Event::Window { timestamp: 0, window_id, win_event }
+    }
+
+    #[test]
+    fn follows_size_and_focus() {
+        let mut state = WindowState::new(1, 640, 480);
+        assert!(state.handle_event(&window_event(1, WindowEvent::SizeChanged(800, 600))));
+        assert_eq!(state.size(), (800, 600));
+        assert!(state.handle_event(&window_event(1, WindowEvent::Leave)));
+        assert!(state.handle_event(&window_event(1, WindowEvent::FocusLost)));
+        assert!(!state.has_mouse_focus() && !state.has_keyboard_focus());
+        assert!(!state.handle_event(&window_event(1, WindowEvent::Moved(10, 20))));
+        assert_eq!(state.position(), (10, 20));
+        // Exposed changes nothing, but the window needs drawing again
+        assert!(state.handle_event(&window_event(1, WindowEvent::Exposed)));
+    }
+
+    #[test]
+    fn minimized_windows_are_not_visible() {
+        let mut state = WindowState::new(1, 640, 480);
+        state.handle_event(&window_event(1, WindowEvent::Maximized));
+        assert!(state.is_maximized());
+        state.handle_event(&window_event(1, WindowEvent::Minimized));
+        assert!(!state.is_visible());
+        state.handle_event(&window_event(1, WindowEvent::Restored));
+        assert!(state.is_visible() && !state.is_maximized());
+        state.handle_event(&window_event(1, WindowEvent::Hidden));
+        assert!(!state.is_visible());
+    }
+
+    #[test]
+    fn other_windows_events_are_ignored() {
+        let mut state = WindowState::new(1, 640, 480);
+        assert!(!state.handle_event(&window_event(2, WindowEvent::Resized(10, 10))));
+        assert!(!state.handle_event(&window_event(2, WindowEvent::Close)));
+        assert_eq!(state.size(), (640, 480));
+        assert!(!state.close_requested());
+        state.handle_event(&window_event(1, WindowEvent::Close));
+        assert!(state.close_requested());
+    }
+
+    #[test]
+    fn events_know_their_window() {
+        assert_eq!(event_window_id(&window_event(3, WindowEvent::Close)), Some(3));
+        let key = Event::KeyDown { timestamp: 0, window_id: 2, keycode: None, scancode: None,
+                                   keymod: ::sdl2::keyboard::NOMOD, repeat: false };
+        assert_eq!(event_window_id(&key), Some(2));
+        assert_eq!(event_window_id(&Event::Quit { timestamp: 0 }), None);
+    }
+
+    // Three windows, with ids 1 to 3, the last one added having the focus
+    fn three_windows() -> WindowStates {
+        let mut states = WindowStates::new();
+        for id in 1..4 {
+            assert_eq!(states.add(WindowState::new(id, 640, 480)), id as usize - 1);
+        }
+        states
+    }
+
+    #[test]
+    fn events_go_to_their_window() {
+        let mut states = three_windows();
+        let routed = states.handle_event(&window_event(2, WindowEvent::Resized(800, 600)));
+        assert_eq!(routed, Some(Routed { index: 1, closed: false }));
+        assert_eq!(states.get(1).map(WindowState::size), Some((800, 600)));
+        assert_eq!(states.get(0).map(WindowState::size), Some((640, 480)));
+        assert_eq!(states.handle_event(&window_event(4, WindowEvent::Exposed)), None);
+        assert_eq!(states.handle_event(&Event::Quit { timestamp: 0 }), None);
+    }
+
+    #[test]
+    fn only_one_window_has_the_focus() {
+        let mut states = three_windows();
+        assert_eq!(states.focused(), Some(2));
+        states.handle_event(&window_event(1, WindowEvent::FocusGained));
+        states.handle_event(&window_event(2, WindowEvent::Enter));
+        assert_eq!(states.focused(), Some(0));
+        let mouse: Vec<bool> = states.iter().map(WindowState::has_mouse_focus).collect();
+        assert_eq!(mouse, vec![false, true, false]);
+        let keyboard: Vec<bool> = states.iter().map(WindowState::has_keyboard_focus).collect();
+        assert_eq!(keyboard, vec![true, false, false]);
+    }
+
+    #[test]
+    fn closing_a_window_hides_it() {
+        let mut states = three_windows();
+        let routed = states.handle_event(&window_event(3, WindowEvent::Close));
+        assert_eq!(routed, Some(Routed { index: 2, closed: true }));
+        let closed = states.get(2).unwrap();
+        assert!(!closed.is_visible() && !closed.has_keyboard_focus());
+        // Ready to be shown again rather than closing straight away
+        assert!(!closed.close_requested());
+        assert_eq!(states.focused(), None);
+        states.handle_event(&window_event(1, WindowEvent::Close));
+        states.handle_event(&window_event(2, WindowEvent::Close));
+        assert!(states.all_hidden() && !states.any_visible());
+    }
+
+    #[test]
+    fn showing_a_window_gives_it_the_focus() {
+        let mut states = three_windows();
+        assert!(states.hide(2));
+        assert!(!states.hide(3));
+        states.handle_event(&window_event(1, WindowEvent::Minimized));
+        assert_eq!(states.iter().filter(|state| state.is_visible()).count(), 1);
+        // Minimized windows aren't hidden, and come back when shown
+        assert!(!states.all_hidden());
+        assert!(states.show(0));
+        assert!(!states.show(3));
+        assert_eq!(states.focused(), Some(0));
+        assert!(states.get(0).unwrap().is_visible());
+        assert!(states.show(2));
+        assert_eq!(states.focused(), Some(2));
+        assert_eq!(states.find_id(3), Some(2));
+        assert_eq!(states.find_id(4), None);
+    }
+}
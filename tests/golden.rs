@@ -0,0 +1,299 @@
+//! Golden-image regression tests for the lessons.
+//!
+//! Each test runs a lesson binary headless - SDL's dummy video driver
+//! and the software renderer, so no display or GPU is needed - with
+//! `--frames` and `--capture` to save the last of a fixed number of
+//! frames, and compares it with the reference PNG in `tests/golden/`.
+//! A lesson that doesn't exit once its frames are drawn fails the test
+//! rather than hanging it.
+//!
+//! To create or update the references after an intentional change in
+//! a lesson's output, run
+//!
+//!     LAZY_FOO_BLESS=1 cargo test --test golden
+//!
+//! and commit the new PNGs.  A lesson with no reference yet is still
+//! run and its frame still captured, but there is nothing to compare
+//! it with; set `LAZY_FOO_REQUIRE_REFERENCES` to make that a failure.
+//! `LAZY_FOO_TOLERANCE` overrides how far (0-255) any color channel of
+//! a pixel may drift before that pixel counts as a mismatch.
+//!
+//! The `_replay` tests play back the input in `tests/replays/` (saved
+//! with `--record`, or written by hand) so that the captured frame
+//! shows the lesson responding to it.
+
+extern crate lazy_foo;
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lazy_foo::capture;
+
+/// Per-channel difference allowed by default.  The software renderer
+/// is deterministic, but blending and scaling round slightly
+/// differently between SDL releases.
+const DEFAULT_TOLERANCE: u8 = 2;
+
+/// How long a lesson gets to draw its frames and exit
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Set to regenerate the reference images instead of checking them
+const BLESS_VAR: &str = "LAZY_FOO_BLESS";
+/// Set to fail tests whose reference image is missing
+const REQUIRE_VAR: &str = "LAZY_FOO_REQUIRE_REFERENCES";
+const TOLERANCE_VAR: &str = "LAZY_FOO_TOLERANCE";
+
+fn reference_path(lesson: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.png", lesson))
+}
+
+fn capture_path(lesson: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("golden")
+        .join(format!("{}.png", lesson))
+}
+
+fn tolerance() -> u8 {
+    match env::var(TOLERANCE_VAR) {
+        Ok(value) => value.parse()
+            .unwrap_or_else(|_| panic!("{} must be a number from 0 to 255", TOLERANCE_VAR)),
+        Err(_) => DEFAULT_TOLERANCE,
+    }
+}
+
+/// Run `exe` headless for `frames` frames and compare its last frame
+/// against the lesson's reference image.
+fn check_lesson(lesson: &str, exe: &str, frames: u32) {
+    check_lesson_with(lesson, exe, frames, &[]);
+}
+
+/// As `check_lesson`, playing back the recorded input in
+/// `tests/replays/<lesson>.ron`.  `name` is the reference image.
+fn check_replay(name: &str, lesson: &str, exe: &str, frames: u32) {
+    let replay = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("replays")
+        .join(format!("{}.ron", lesson));
+    check_lesson_with(name, exe, frames, &["--replay".as_ref(), replay.as_os_str()]);
+}
+
+fn check_lesson_with(lesson: &str, exe: &str, frames: u32, args: &[&OsStr]) {
+    let actual = capture_path(lesson);
+    fs::create_dir_all(actual.parent().unwrap()).unwrap();
+    let _ = fs::remove_file(&actual);
+
+    // Lessons load their assets relative to the repository root
+    let mut child = Command::new(exe)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("SDL_VIDEODRIVER", "dummy")
+        .arg("--software")
+        .arg("--frames").arg(frames.to_string())
+        .arg("--capture").arg(&actual)
+        .args(args)
+        .spawn()
+        .unwrap_or_else(|err| panic!("could not run {}: {}", exe, err));
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if started.elapsed() > TIMEOUT {
+            let _ = child.kill();
+            panic!("{} was still running {}s after being asked for {} frames",
+                   lesson, TIMEOUT.as_secs(), frames);
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    assert!(status.success(), "{} exited with {}", lesson, status);
+
+    // Whatever happens next, the frame has to be there and readable
+    let (aw, ah, actual_pixels) = capture::load_rgba(&actual)
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    let expected = reference_path(lesson);
+    if env::var_os(BLESS_VAR).is_some() {
+        fs::create_dir_all(expected.parent().unwrap()).unwrap();
+        fs::copy(&actual, &expected).unwrap();
+        return;
+    }
+    if !expected.exists() {
+        assert!(env::var_os(REQUIRE_VAR).is_none(),
+                "no reference image for {} at {}; the captured frame is at {}.  \
+                 Check it, then rerun with {}=1 to make it the reference.",
+                lesson, expected.display(), actual.display(), BLESS_VAR);
+        eprintln!("{}: no reference image yet, so the {}x{} frame at {} was not compared",
+                  lesson, aw, ah, actual.display());
+        return;
+    }
+
+    let (ew, eh, expected_pixels) = capture::load_rgba(&expected)
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert_eq!((aw, ah), (ew, eh), "{} rendered a frame of the wrong size", lesson);
+
+    let tolerance = tolerance();
+    let comparison = capture::compare_rgba(&actual_pixels, &expected_pixels, tolerance);
+    assert!(comparison.mismatched == 0,
+            "{}: {} of {} pixels differ from {} by more than {} (largest difference {}); \
+             the captured frame is at {}",
+            lesson, comparison.mismatched, aw * ah, expected.display(), tolerance,
+            comparison.max_delta, actual.display());
+}
+
+#[test]
+fn lesson01() { check_lesson("lesson01", env!("CARGO_BIN_EXE_lesson01"), 1); }
+
+#[test]
+fn lesson02() { check_lesson("lesson02", env!("CARGO_BIN_EXE_lesson02"), 1); }
+
+#[test]
+fn lesson03() { check_lesson("lesson03", env!("CARGO_BIN_EXE_lesson03"), 2); }
+
+#[test]
+fn lesson04() { check_lesson("lesson04", env!("CARGO_BIN_EXE_lesson04"), 2); }
+
+#[test]
+fn lesson05() { check_lesson("lesson05", env!("CARGO_BIN_EXE_lesson05"), 2); }
+
+#[test]
+fn lesson06() { check_lesson("lesson06", env!("CARGO_BIN_EXE_lesson06"), 2); }
+
+#[test]
+fn lesson07() { check_lesson("lesson07", env!("CARGO_BIN_EXE_lesson07"), 2); }
+
+#[test]
+fn lesson08() { check_lesson("lesson08", env!("CARGO_BIN_EXE_lesson08"), 2); }
+
+#[test]
+fn lesson09() { check_lesson("lesson09", env!("CARGO_BIN_EXE_lesson09"), 2); }
+
+#[test]
+fn lesson10() { check_lesson("lesson10", env!("CARGO_BIN_EXE_lesson10"), 2); }
+
+#[test]
+fn lesson11() { check_lesson("lesson11", env!("CARGO_BIN_EXE_lesson11"), 2); }
+
+#[test]
+fn lesson12() { check_lesson("lesson12", env!("CARGO_BIN_EXE_lesson12"), 2); }
+
+#[test]
+fn lesson13() { check_lesson("lesson13", env!("CARGO_BIN_EXE_lesson13"), 2); }
+
+// Three frames in, so the capture shows the third walking frame
+#[test]
+fn lesson14() { check_lesson("lesson14", env!("CARGO_BIN_EXE_lesson14"), 3); }
+
+#[test]
+fn lesson15() { check_lesson("lesson15", env!("CARGO_BIN_EXE_lesson15"), 2); }
+
+// The arrow turned right and flipped upside down
+#[test]
+fn lesson15_replay() {
+    check_replay("lesson15_replay", "lesson15", env!("CARGO_BIN_EXE_lesson15"), 3);
+}
+
+#[test]
+fn lesson16() { check_lesson("lesson16", env!("CARGO_BIN_EXE_lesson16"), 2); }
+
+#[test]
+fn lesson17() { check_lesson("lesson17", env!("CARGO_BIN_EXE_lesson17"), 2); }
+
+// One button hovered over and left again, and another held down
+#[test]
+fn lesson17_replay() {
+    check_replay("lesson17_replay", "lesson17", env!("CARGO_BIN_EXE_lesson17"), 3);
+}
+
+#[test]
+fn lesson18() { check_lesson("lesson18", env!("CARGO_BIN_EXE_lesson18"), 2); }
+
+#[test]
+fn lesson19() { check_lesson("lesson19", env!("CARGO_BIN_EXE_lesson19"), 2); }
+
+// A controller that isn't there, its stick pushed down and left
+#[test]
+fn lesson19_replay() {
+    check_replay("lesson19_replay", "lesson19", env!("CARGO_BIN_EXE_lesson19"), 3);
+}
+
+#[test]
+fn lesson32() { check_lesson("lesson32", env!("CARGO_BIN_EXE_lesson32"), 2); }
+
+// Copied, pasted, edited, and an accent still being composed
+#[test]
+fn lesson32_replay() {
+    check_replay("lesson32_replay", "lesson32", env!("CARGO_BIN_EXE_lesson32"), 3);
+}
+
+#[test]
+fn lesson35() { check_lesson("lesson35", env!("CARGO_BIN_EXE_lesson35"), 2); }
+
+// The focus lost, then minimized and restored: the two frames while
+// it was minimized aren't drawn
+#[test]
+fn lesson35_replay() {
+    check_replay("lesson35_replay", "lesson35", env!("CARGO_BIN_EXE_lesson35"), 3);
+}
+
+#[test]
+fn lesson36() { check_lesson("lesson36", env!("CARGO_BIN_EXE_lesson36"), 2); }
+
+// The events inspector is closed, then 3 brings it back with the focus
+#[test]
+fn lesson36_replay() {
+    check_replay("lesson36_replay", "lesson36", env!("CARGO_BIN_EXE_lesson36"), 3);
+}
+
+#[test]
+fn lesson37() { check_lesson("lesson37", env!("CARGO_BIN_EXE_lesson37"), 2); }
+
+#[test]
+fn lesson39() { check_lesson("lesson39", env!("CARGO_BIN_EXE_lesson39"), 2); }
+
+// Right along the top, then down past the first wall, with the camera
+// following the dot
+#[test]
+fn lesson39_replay() {
+    check_replay("lesson39_replay", "lesson39", env!("CARGO_BIN_EXE_lesson39"), 70);
+}
+
+#[test]
+fn lesson40() { check_lesson("lesson40", env!("CARGO_BIN_EXE_lesson40"), 2); }
+
+#[test]
+fn lesson41() { check_lesson("lesson41", env!("CARGO_BIN_EXE_lesson41"), 2); }
+
+// Far enough in for foo to have taken a step
+#[test]
+fn lesson42() { check_lesson("lesson42", env!("CARGO_BIN_EXE_lesson42"), 6); }
+
+#[test]
+fn lesson43() { check_lesson("lesson43", env!("CARGO_BIN_EXE_lesson43"), 2); }
+
+#[test]
+fn lesson54() { check_lesson("lesson54", env!("CARGO_BIN_EXE_lesson54"), 2); }
+
+// Fingers down, moved and lifted, and the mouse pressed as a finger
+#[test]
+fn lesson54_replay() {
+    check_replay("lesson54_replay", "lesson54", env!("CARGO_BIN_EXE_lesson54"), 3);
+}
+
+#[test]
+fn lesson55() { check_lesson("lesson55", env!("CARGO_BIN_EXE_lesson55"), 2); }
+
+// A pinch and turn from two fingers, then a right drag of the mouse
+#[test]
+fn lesson55_replay() {
+    check_replay("lesson55_replay", "lesson55", env!("CARGO_BIN_EXE_lesson55"), 3);
+}
+
+#[test]
+fn launcher() { check_lesson("launcher", env!("CARGO_BIN_EXE_launcher"), 2); }